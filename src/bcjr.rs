@@ -7,8 +7,10 @@ pub trait BcjrDecoder {
     /// * `L_u` is the `systematic` part,
     /// * `L_v` is the `parity` part, and
     /// * `L_a` is the `a-priori` part.
-    /// All parts must have the same number of elements.
-    /// The returned a-posteriori values has the same length as the input.
+    ///
+    /// The `systematic` and `a-priori` parts must have the same number of elements,
+    /// and the `parity` part has `parity_outputs()` consecutive elements for each of them.
+    /// The returned a-posteriori values has the same length as the systematic part.
     fn decode<Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
         &self,
         systematic: Lu,
@@ -16,4 +18,106 @@ pub trait BcjrDecoder {
         apriori: La,
         terminated: bool,
     ) -> Vec<Llr>;
+
+    /// The number of parity outputs of the code for each systematic bit.
+    fn parity_outputs(&self) -> usize {
+        1
+    }
+
+    /// Decode a block like `decode`, where decoders that split the block into sub-blocks
+    /// may use and update the state metrics at the sub-block `boundaries`.
+    /// The boundaries are kept between calls, i.e. between the iterations of a Turbo decoder.
+    fn decode_with_boundaries<
+        Lu: Iterator<Item = Llr>,
+        Lv: Iterator<Item = Llr>,
+        La: Iterator<Item = Llr>,
+    >(
+        &self,
+        systematic: Lu,
+        parity: Lv,
+        apriori: La,
+        terminated: bool,
+        boundaries: &mut BoundaryMetrics,
+    ) -> Vec<Llr> {
+        let _ = boundaries;
+        self.decode(systematic, parity, apriori, terminated)
+    }
+}
+
+/// The state metrics at the sub-block boundaries from the previous decoding of a block,
+/// used to initialize the recursions of the next (next iteration initialization).
+/// The metrics are stored in state order for each sub-block, such that any trellis can use them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoundaryMetrics {
+    /// The number of states of the trellis.
+    states: usize,
+    /// The alphas at the start of each sub-block.
+    alphas: Vec<i16>,
+    /// The betas at the end of each sub-block.
+    betas: Vec<i16>,
+}
+
+impl BoundaryMetrics {
+    pub const fn new() -> Self {
+        Self {
+            states: 0,
+            alphas: vec![],
+            betas: vec![],
+        }
+    }
+
+    /// Whether there are any stored metrics.
+    pub fn is_empty(&self) -> bool {
+        self.alphas.is_empty() && self.betas.is_empty()
+    }
+
+    /// Forget the stored metrics, before decoding a new block.
+    pub fn clear(&mut self) {
+        self.states = 0;
+        self.alphas.clear();
+        self.betas.clear();
+    }
+
+    /// Reset to equiprobable metrics for `sub_blocks` sub-blocks of a trellis with `states` states.
+    pub fn reset(&mut self, states: usize, sub_blocks: usize) {
+        self.states = states;
+        self.alphas.clear();
+        self.alphas.resize(states * sub_blocks, 0);
+        self.betas.clear();
+        self.betas.resize(states * sub_blocks, 0);
+    }
+
+    /// The number of states of the trellis.
+    pub fn states(&self) -> usize {
+        self.states
+    }
+
+    /// The number of sub-blocks.
+    pub fn sub_blocks(&self) -> usize {
+        self.alphas.len().checked_div(self.states).unwrap_or(0)
+    }
+
+    /// The alphas at the start of `sub_block`.
+    pub fn alphas(&self, sub_block: usize) -> &[i16] {
+        &self.alphas[sub_block * self.states..(sub_block + 1) * self.states]
+    }
+
+    /// The betas at the end of `sub_block`.
+    pub fn betas(&self, sub_block: usize) -> &[i16] {
+        &self.betas[sub_block * self.states..(sub_block + 1) * self.states]
+    }
+
+    /// Store the metrics produced when decoding `sub_block`,
+    /// i.e. the `alphas` at its end, which are the alphas at the start of the next sub-block,
+    /// and the `betas` at its start, which are the betas at the end of the previous sub-block.
+    /// The metrics at the ends of the block are not stored.
+    pub fn store(&mut self, sub_block: usize, alphas: &[i16], betas: &[i16]) {
+        let states = self.states;
+        if sub_block + 1 < self.sub_blocks() {
+            self.alphas[(sub_block + 1) * states..(sub_block + 2) * states].copy_from_slice(alphas);
+        }
+        if sub_block > 0 {
+            self.betas[(sub_block - 1) * states..sub_block * states].copy_from_slice(betas);
+        }
+    }
 }
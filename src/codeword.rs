@@ -0,0 +1,198 @@
+use alloc::vec::Vec;
+
+/// The number of tail bits per `d` stream.
+const STREAM_TAIL_LEN: usize = 4;
+
+/// A Turbo codeword split into the parts consumed by the Turbo decoder.
+/// The element type is `bool` for transmitted bits and `Llr` for received soft values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TurboCodeword<T> {
+    /// The systematic part, i.e. the input bits.
+    pub systematic: Vec<T>,
+    /// The systematic termination from the first encoder.
+    pub first_systematic_termination: Vec<T>,
+    /// The parity from the first encoder (also includes termination parity).
+    pub first_parity: Vec<T>,
+    /// The systematic termination from the second encoder.
+    pub second_systematic_termination: Vec<T>,
+    /// The parity from the second encoder (also includes termination parity).
+    pub second_parity: Vec<T>,
+}
+
+/// The part of the codeword that holds a `d` stream element.
+enum Part {
+    Systematic,
+    FirstSystematicTermination,
+    FirstParity,
+    SecondSystematicTermination,
+    SecondParity,
+}
+
+impl<T: Copy> TurboCodeword<T> {
+    /// Create a codeword for block length `k` with all elements set to `value`.
+    pub fn from_elem(k: usize, value: T) -> Self {
+        Self {
+            systematic: vec![value; k],
+            first_systematic_termination: vec![value; 3],
+            first_parity: vec![value; k + 3],
+            second_systematic_termination: vec![value; 3],
+            second_parity: vec![value; k + 3],
+        }
+    }
+
+    /// Create a codeword from the three `d` streams, see 36.212 5.1.3.2.2.
+    /// Each stream has length `K+4`.
+    pub fn from_streams(d0: &[T], d1: &[T], d2: &[T]) -> Self {
+        assert_eq!(d0.len(), d1.len());
+        assert_eq!(d0.len(), d2.len());
+        assert!(d0.len() >= STREAM_TAIL_LEN);
+
+        let k = d0.len() - STREAM_TAIL_LEN;
+        let mut codeword = Self::from_elem(k, d0[0]);
+        for (stream, d) in [d0, d1, d2].iter().enumerate() {
+            for (index, &value) in d.iter().enumerate() {
+                *codeword.d_mut(stream, index) = value;
+            }
+        }
+        codeword
+    }
+
+    /// The block length `K`.
+    pub fn len(&self) -> usize {
+        self.systematic.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systematic.is_empty()
+    }
+
+    /// Get element `index` in the `d` stream `stream` (0, 1 or 2), see 36.212 5.1.3.2.2.
+    pub fn d(&self, stream: usize, index: usize) -> T {
+        let (part, index) = self.locate(stream, index);
+        match part {
+            Part::Systematic => self.systematic[index],
+            Part::FirstSystematicTermination => self.first_systematic_termination[index],
+            Part::FirstParity => self.first_parity[index],
+            Part::SecondSystematicTermination => self.second_systematic_termination[index],
+            Part::SecondParity => self.second_parity[index],
+        }
+    }
+
+    /// Get a mutable reference to element `index` in the `d` stream `stream` (0, 1 or 2).
+    pub fn d_mut(&mut self, stream: usize, index: usize) -> &mut T {
+        let (part, index) = self.locate(stream, index);
+        match part {
+            Part::Systematic => &mut self.systematic[index],
+            Part::FirstSystematicTermination => &mut self.first_systematic_termination[index],
+            Part::FirstParity => &mut self.first_parity[index],
+            Part::SecondSystematicTermination => &mut self.second_systematic_termination[index],
+            Part::SecondParity => &mut self.second_parity[index],
+        }
+    }
+
+    /// Get the `d0` stream, see 36.212 5.1.3.2.2.
+    /// It consists of the systematic part followed by the tail `x_K, z_K+1, x'_K, z'_K+1`.
+    pub fn d0(&self) -> Vec<T> {
+        self.stream(0)
+    }
+
+    /// Get the `d1` stream, see 36.212 5.1.3.2.2.
+    /// It consists of the first parity followed by the tail `z_K, x_K+2, z'_K, x'_K+2`.
+    pub fn d1(&self) -> Vec<T> {
+        self.stream(1)
+    }
+
+    /// Get the `d2` stream, see 36.212 5.1.3.2.2.
+    /// It consists of the second parity followed by the tail `x_K+1, z_K+2, x'_K+1, z'_K+2`.
+    pub fn d2(&self) -> Vec<T> {
+        self.stream(2)
+    }
+
+    fn stream(&self, stream: usize) -> Vec<T> {
+        (0..self.len() + STREAM_TAIL_LEN)
+            .map(|index| self.d(stream, index))
+            .collect()
+    }
+
+    fn locate(&self, stream: usize, index: usize) -> (Part, usize) {
+        let k = self.len();
+        if index < k {
+            return match stream {
+                0 => (Part::Systematic, index),
+                1 => (Part::FirstParity, index),
+                2 => (Part::SecondParity, index),
+                _ => panic!("Invalid stream"),
+            };
+        }
+
+        match (stream, index - k) {
+            (0, 0) => (Part::FirstSystematicTermination, 0),
+            (0, 1) => (Part::FirstParity, k + 1),
+            (0, 2) => (Part::SecondSystematicTermination, 0),
+            (0, 3) => (Part::SecondParity, k + 1),
+            (1, 0) => (Part::FirstParity, k),
+            (1, 1) => (Part::FirstSystematicTermination, 2),
+            (1, 2) => (Part::SecondParity, k),
+            (1, 3) => (Part::SecondSystematicTermination, 2),
+            (2, 0) => (Part::FirstSystematicTermination, 1),
+            (2, 1) => (Part::FirstParity, k + 2),
+            (2, 2) => (Part::SecondSystematicTermination, 1),
+            (2, 3) => (Part::SecondParity, k + 2),
+            _ => panic!("Invalid stream or index"),
+        }
+    }
+}
+
+/// A duo-binary Turbo codeword split into the parts consumed by the duo-binary Turbo decoder.
+/// The element type is `bool` for transmitted bits and `Llr` for received soft values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuoBinaryCodeword<T> {
+    /// The systematic `A` bits of the couples.
+    pub a: Vec<T>,
+    /// The systematic `B` bits of the couples.
+    pub b: Vec<T>,
+    /// The `Y` parity from the first encoder.
+    pub first_y: Vec<T>,
+    /// The `W` parity from the first encoder.
+    pub first_w: Vec<T>,
+    /// The `Y` parity from the second encoder.
+    pub second_y: Vec<T>,
+    /// The `W` parity from the second encoder.
+    pub second_w: Vec<T>,
+}
+
+impl<T> DuoBinaryCodeword<T> {
+    /// The number of couples `N`.
+    pub fn len(&self) -> usize {
+        self.a.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.a.is_empty()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_roundtrip() {
+        let k = 8;
+        let d0: Vec<u16> = (0..k as u16 + 4).collect();
+        let d1: Vec<u16> = (100..100 + k as u16 + 4).collect();
+        let d2: Vec<u16> = (200..200 + k as u16 + 4).collect();
+
+        let codeword = TurboCodeword::from_streams(&d0, &d1, &d2);
+
+        assert_eq!(vec![8, 208, 109], codeword.first_systematic_termination);
+        assert_eq!(vec![10, 210, 111], codeword.second_systematic_termination);
+        assert_eq!(
+            vec![100, 101, 102, 103, 104, 105, 106, 107, 108, 9, 209],
+            codeword.first_parity
+        );
+        assert_eq!(d0, codeword.d0());
+        assert_eq!(d1, codeword.d1());
+        assert_eq!(d2, codeword.d2());
+    }
+}
@@ -0,0 +1,149 @@
+//! Cyclic Redundancy Check, see 36.212 5.1.1.
+use crate::Llr;
+use alloc::vec::Vec;
+
+/// Cyclic Redundancy Check engine for a generator polynomial of up to 32 bits.
+/// The register is initialized to zero and the bits are processed MSB first without reflection,
+/// such that the parity bits attached after the data make the remainder of the whole block zero.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crc {
+    /// The generator polynomial without the leading `D^width` term.
+    poly: u32,
+    /// The number of parity bits.
+    width: u8,
+}
+
+impl Crc {
+    /// `gCRC24A(D) = D^24 + D^23 + D^18 + D^17 + D^14 + D^11 + D^10 + D^7 + D^6 + D^5 + D^4 + D^3 + D + 1`.
+    pub const CRC24A: Crc = Crc::new(0x864CFB, 24);
+    /// `gCRC24B(D) = D^24 + D^23 + D^6 + D^5 + D + 1`.
+    pub const CRC24B: Crc = Crc::new(0x800063, 24);
+    /// `gCRC16(D) = D^16 + D^12 + D^5 + 1`.
+    pub const CRC16: Crc = Crc::new(0x1021, 16);
+    /// `gCRC8(D) = D^8 + D^7 + D^4 + D^3 + D + 1`.
+    pub const CRC8: Crc = Crc::new(0x9B, 8);
+
+    /// Create a CRC engine for the generator polynomial `poly` with `width` parity bits.
+    /// The polynomial is given without the leading `D^width` term.
+    pub const fn new(poly: u32, width: u8) -> Self {
+        assert!(width >= 1 && width <= 32);
+        Self { poly, width }
+    }
+
+    /// The number of parity bits.
+    pub const fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    const fn mask(&self) -> u32 {
+        u32::MAX >> (32 - self.width)
+    }
+
+    /// Update the `crc` register with a single bit.
+    #[inline]
+    const fn update_bit(&self, crc: u32, bit: bool) -> u32 {
+        let msb = (crc >> (self.width - 1)) & 1 == 1;
+        let crc = (crc << 1) & self.mask();
+        if msb ^ bit {
+            crc ^ self.poly
+        } else {
+            crc
+        }
+    }
+
+    /// Compute the parity bits for a sequence of `bits`.
+    pub fn checksum_bits<B: IntoIterator<Item = bool>>(&self, bits: B) -> u32 {
+        bits.into_iter()
+            .fold(0, |crc, bit| self.update_bit(crc, bit))
+    }
+
+    /// Compute the parity bits for packed `bytes`, where the MSB of each byte is the first bit.
+    pub fn checksum(&self, bytes: &[u8]) -> u32 {
+        self.checksum_bits(
+            bytes
+                .iter()
+                .flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1)),
+        )
+    }
+
+    /// Compute the parity bits for the hard decisions of a sequence of `llrs`.
+    pub fn checksum_llrs(&self, llrs: &[Llr]) -> u32 {
+        self.checksum_bits(llrs.iter().map(|llr| llr.hard()))
+    }
+
+    /// Attach the parity bits to `bits`, MSB first.
+    pub fn attach_bits(&self, bits: &mut Vec<bool>) {
+        let crc = self.checksum_bits(bits.iter().copied());
+        bits.extend((0..self.width).rev().map(|i| (crc >> i) & 1 == 1));
+    }
+
+    /// Attach the parity bits to packed `bytes`, MSB first.
+    /// The width of the CRC must be a multiple of 8.
+    pub fn attach(&self, bytes: &mut Vec<u8>) {
+        assert_eq!(0, self.width % 8);
+        let crc = self.checksum(bytes);
+        bytes.extend((0..self.width / 8).rev().map(|i| (crc >> (8 * i)) as u8));
+    }
+
+    /// Check `bits` that has the parity bits attached.
+    pub fn check_bits(&self, bits: &[bool]) -> bool {
+        self.checksum_bits(bits.iter().copied()) == 0
+    }
+
+    /// Check packed `bytes` that has the parity bits attached.
+    pub fn check(&self, bytes: &[u8]) -> bool {
+        self.checksum(bytes) == 0
+    }
+
+    /// Check the hard decisions of `llrs` that has the parity bits attached.
+    pub fn check_llrs(&self, llrs: &[Llr]) -> bool {
+        self.checksum_llrs(llrs) == 0
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    const CHECK: &[u8] = b"123456789";
+
+    #[test]
+    fn checksum() {
+        assert_eq!(0xCDE703, Crc::CRC24A.checksum(CHECK));
+        assert_eq!(0x23EF52, Crc::CRC24B.checksum(CHECK));
+        assert_eq!(0x31C3, Crc::CRC16.checksum(CHECK));
+        assert_eq!(0xEA, Crc::CRC8.checksum(CHECK));
+    }
+
+    #[test]
+    fn attach_and_check() {
+        for crc in [Crc::CRC24A, Crc::CRC24B, Crc::CRC16, Crc::CRC8].iter() {
+            let mut bytes = CHECK.to_vec();
+            crc.attach(&mut bytes);
+            assert_eq!(CHECK.len() + crc.width() / 8, bytes.len());
+            assert!(crc.check(&bytes));
+
+            bytes[3] ^= 0x10;
+            assert!(!crc.check(&bytes));
+        }
+    }
+
+    #[test]
+    fn attach_and_check_bits() {
+        let crc = Crc::new(0b101, 3);
+        let mut bits = vec![true, false, true, true, false, false, true];
+        crc.attach_bits(&mut bits);
+        assert_eq!(10, bits.len());
+        assert!(crc.check_bits(&bits));
+
+        let llrs: Vec<Llr> = bits
+            .iter()
+            .map(|&x| Llr(if x { 10 } else { -10 }))
+            .collect();
+        assert!(crc.check_llrs(&llrs));
+        assert_eq!(
+            crc.checksum_bits(bits[..7].iter().copied()),
+            crc.checksum_llrs(&llrs[..7])
+        );
+    }
+}
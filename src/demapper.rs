@@ -0,0 +1,259 @@
+//! Soft Demapping of Equalized Symbols, see 36.211 7.1.
+use crate::Llr;
+use alloc::vec::Vec;
+
+/// The modulation schemes with their 3GPP Gray mappings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Modulation {
+    Bpsk,
+    Qpsk,
+    Qam16,
+    Qam64,
+    Qam256,
+}
+
+impl Modulation {
+    /// The number of bits carried by each symbol.
+    pub const fn bits_per_symbol(&self) -> usize {
+        match self {
+            Modulation::Bpsk => 1,
+            Modulation::Qpsk => 2,
+            Modulation::Qam16 => 4,
+            Modulation::Qam64 => 6,
+            Modulation::Qam256 => 8,
+        }
+    }
+
+    /// The half distance between two neighbouring amplitude levels for the unit power constellation,
+    /// i.e. the amplitude levels are odd multiples of this value.
+    pub const fn level(&self) -> i16 {
+        match self {
+            // 1/sqrt(2)
+            Modulation::Bpsk | Modulation::Qpsk => 2896,
+            // 1/sqrt(10)
+            Modulation::Qam16 => 1295,
+            // 1/sqrt(42)
+            Modulation::Qam64 => 632,
+            // 1/sqrt(170)
+            Modulation::Qam256 => 314,
+        }
+    }
+}
+
+/// An equalized complex symbol in fixed-point, where `Iq::ONE` is the unit amplitude.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Iq {
+    pub i: i16,
+    pub q: i16,
+}
+
+impl Iq {
+    /// The fixed-point unit amplitude, leaving room for noisy symbols up to an amplitude of 8.
+    pub const ONE: i16 = 1 << 12;
+
+    pub const fn new(i: i16, q: i16) -> Self {
+        Self { i, q }
+    }
+}
+
+/// The number of fractional bits in the produced llr's, matching the log-MAP correction table.
+const LLR_FRACTIONAL_BITS: u32 = 2;
+
+/// The number of fractional bits in the precomputed gain.
+const GAIN_FRACTIONAL_BITS: u32 = 16;
+
+/// Max-log soft demapper using the piecewise linear approximation of the bit llr's.
+/// The llr's are computed independently for the in-phase and quadrature components,
+/// and are saturated to `±127`.
+#[derive(Clone, Debug)]
+pub struct Demapper {
+    modulation: Modulation,
+    /// The factor `4 * level / N0` with `GAIN_FRACTIONAL_BITS` + `LLR_FRACTIONAL_BITS` fractional bits.
+    gain: i64,
+}
+
+impl Demapper {
+    /// Create a demapper for `modulation`, where the `noise_variance` is the complex noise variance `N0`
+    /// in units of `Iq::ONE^2`, i.e. `1 << 24` corresponds to a variance of 1.
+    pub fn new(modulation: Modulation, noise_variance: u32) -> Self {
+        assert!(noise_variance > 0);
+        let numerator =
+            (4 * modulation.level() as i64) << (GAIN_FRACTIONAL_BITS + LLR_FRACTIONAL_BITS);
+        let noise_variance = noise_variance as i64;
+        Self {
+            modulation,
+            gain: (numerator + noise_variance / 2) / noise_variance,
+        }
+    }
+
+    pub const fn modulation(&self) -> Modulation {
+        self.modulation
+    }
+
+    /// Scale and saturate the distance `t` from a decision boundary.
+    #[inline]
+    fn llr(&self, t: i32) -> Llr {
+        let llr =
+            (self.gain * t as i64 + (1 << (GAIN_FRACTIONAL_BITS - 1))) >> GAIN_FRACTIONAL_BITS;
+        Llr(llr.clamp(-(i8::MAX as i64), i8::MAX as i64) as i8)
+    }
+
+    /// Demap a single real dimension `y` carrying `bits` bits,
+    /// writing the llr's to every second element of `llrs`.
+    #[inline]
+    fn demap_dimension(&self, y: i16, bits: usize, llrs: &mut [Llr]) {
+        let level = self.modulation.level() as i32;
+        let y = y as i32;
+
+        // The first bit is 0 for positive amplitudes.
+        llrs[0] = self.llr(-y);
+
+        // The remaining bits select between the outer and inner halves of the remaining levels.
+        let mut t = y.abs();
+        for k in 1..bits {
+            t -= level << (bits - k);
+            llrs[2 * k] = self.llr(t);
+            t = t.abs();
+        }
+    }
+
+    /// Demap a single `symbol` into `llrs`, which must have the length `bits_per_symbol()`.
+    pub fn demap_symbol(&self, symbol: Iq, llrs: &mut [Llr]) {
+        assert_eq!(self.modulation.bits_per_symbol(), llrs.len());
+        match self.modulation {
+            Modulation::Bpsk => {
+                llrs[0] = self.llr(-(symbol.i as i32 + symbol.q as i32));
+            }
+            modulation => {
+                let bits = modulation.bits_per_symbol() / 2;
+                self.demap_dimension(symbol.i, bits, llrs);
+                self.demap_dimension(symbol.q, bits, &mut llrs[1..]);
+            }
+        }
+    }
+
+    /// Demap `symbols` into `llrs`, which must have the length `bits_per_symbol()` times the number of symbols.
+    pub fn demap_into(&self, symbols: &[Iq], llrs: &mut [Llr]) {
+        let bits = self.modulation.bits_per_symbol();
+        assert_eq!(symbols.len() * bits, llrs.len());
+        for (&symbol, llrs) in symbols.iter().zip(llrs.chunks_exact_mut(bits)) {
+            self.demap_symbol(symbol, llrs);
+        }
+    }
+
+    /// Demap `symbols` into a new vector of llr's.
+    pub fn demap(&self, symbols: &[Iq]) -> Vec<Llr> {
+        let mut llrs = vec![Llr::ZERO; symbols.len() * self.modulation.bits_per_symbol()];
+        self.demap_into(symbols, &mut llrs);
+        llrs
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::llr_vec;
+
+    const MODULATIONS: [Modulation; 5] = [
+        Modulation::Bpsk,
+        Modulation::Qpsk,
+        Modulation::Qam16,
+        Modulation::Qam64,
+        Modulation::Qam256,
+    ];
+
+    /// The amplitude in units of `level` for the bits of a single dimension,
+    /// `(1-2b0)[2^(m-1) - (1-2b1)[2^(m-2) - ... (1-2b(m-1))]]`, see 38.211 5.1.
+    fn amplitude(bits: &[bool]) -> i16 {
+        let sign = |bit: bool| if bit { -1 } else { 1 };
+        let m = bits.len();
+        let mut value = 1;
+        for k in (1..m).rev() {
+            value = (1 << (m - k)) - sign(bits[k]) * value;
+        }
+        sign(bits[0]) * value
+    }
+
+    fn modulate(modulation: Modulation, bits: &[bool]) -> Iq {
+        let level = modulation.level();
+        if modulation == Modulation::Bpsk {
+            let x = amplitude(bits) * level;
+            Iq::new(x, x)
+        } else {
+            let i: Vec<bool> = bits.iter().step_by(2).copied().collect();
+            let q: Vec<bool> = bits.iter().skip(1).step_by(2).copied().collect();
+            Iq::new(amplitude(&i) * level, amplitude(&q) * level)
+        }
+    }
+
+    #[test]
+    fn mapping() {
+        // 64QAM in-phase amplitudes for b0b2b4 = 000, 001, 010 and 011, see 36.211 Table 7.1.4-1.
+        assert_eq!(3, amplitude(&[false, false, false]));
+        assert_eq!(1, amplitude(&[false, false, true]));
+        assert_eq!(5, amplitude(&[false, true, false]));
+        assert_eq!(7, amplitude(&[false, true, true]));
+        assert_eq!(-7, amplitude(&[true, true, true]));
+        assert_eq!(5, amplitude(&[false, false, false, false]));
+    }
+
+    #[test]
+    fn hard_decisions() {
+        for &modulation in MODULATIONS.iter() {
+            let bits_per_symbol = modulation.bits_per_symbol();
+            let demapper = Demapper::new(modulation, 1 << 20);
+
+            for x in 0..1u16 << bits_per_symbol {
+                let bits: Vec<bool> = (0..bits_per_symbol).map(|i| (x >> i) & 1 == 1).collect();
+                let llrs = demapper.demap(&[modulate(modulation, &bits)]);
+                let decided: Vec<bool> = llrs.iter().map(|x| x.hard()).collect();
+                assert_eq!(bits, decided);
+                assert!(llrs.iter().all(|x| x.0 != 0));
+            }
+        }
+    }
+
+    #[test]
+    fn qpsk() {
+        let demapper = Demapper::new(Modulation::Qpsk, 1 << 24);
+        let symbols = [Iq::new(2896, -2896), Iq::new(0, 1448)];
+        assert_eq!(llr_vec![-8, 8, 0, -4], demapper.demap(&symbols));
+    }
+
+    #[test]
+    fn saturation() {
+        let demapper = Demapper::new(Modulation::Qam64, 1 << 14);
+        let llrs = demapper.demap(&[Iq::new(-7 * 632, 7 * 632)]);
+        assert_eq!(llr_vec![127, -127, 127, 127, 127, 127], llrs);
+    }
+
+    #[test]
+    fn max_log() {
+        // The exact max-log llr for a single dimension, in units of 1/4 nat.
+        let noise_variance = 1i64 << 23;
+        let exact = |y: i64, bit: usize| -> i64 {
+            let level = Modulation::Qam16.level() as i64;
+            let (mut d0, mut d1) = (i64::MAX, i64::MAX);
+            for x in 0..4u8 {
+                let bits = [x & 1 != 0, x & 2 != 0];
+                let distance = (y - amplitude(&bits) as i64 * level).pow(2);
+                if bits[bit] {
+                    d1 = d1.min(distance);
+                } else {
+                    d0 = d0.min(distance);
+                }
+            }
+            4 * (d0 - d1) / noise_variance
+        };
+
+        let demapper = Demapper::new(Modulation::Qam16, noise_variance as u32);
+        for &y in [-4000i16, -2000, -1000, -100, 0, 300, 1295, 2000, 3885].iter() {
+            let llrs = demapper.demap(&[Iq::new(y, 0)]);
+            // The piecewise linear approximation is exact for the second bit and close to the boundary for the first.
+            assert!((llrs[2].0 as i64 - exact(y as i64, 1)).abs() <= 1);
+            if (y as i32).abs() <= 2 * 1295 {
+                assert!((llrs[0].0 as i64 - exact(y as i64, 0)).abs() <= 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{
+    dword::DWord,
+    interleavers::{ctc::CtcStandard, Interleaver},
+    trellises::duobinary::{CircularMetrics, DuoBinaryTrellis},
+    DuoBinaryCodeword, ExtrinsicScale, Llr, SymbolLlr,
+};
+use alloc::vec::Vec;
+use streaming_iterator::StreamingIterator;
+
+/// A duo-binary Turbo decoder, exchanging symbol log-likelihoods between the constituent decoders.
+pub struct DuoBinaryTurboDecoder {
+    /// The constituent decoder.
+    bcjr: DuoBinaryTrellis,
+    /// The a-priori symbol log-likelihoods for the first decoder (produced by the second decoder).
+    la_first: Vec<SymbolLlr>,
+    /// The de-interleaved a-posteriori symbol log-likelihoods (produced by the second decoder).
+    l_app_deinterleaved: Vec<SymbolLlr>,
+    /// The extrinsic scaling factors for each iteration.
+    extrinsic_scale: Vec<ExtrinsicScale>,
+    /// The circular state metrics kept between iterations by the first decoder.
+    first_circular: CircularMetrics,
+    /// The circular state metrics kept between iterations by the second decoder.
+    second_circular: CircularMetrics,
+}
+
+pub struct DuoBinaryTurboDecodeIterator<'a, I: Interleaver> {
+    /// Reference to the decoder.
+    decoder: &'a mut DuoBinaryTurboDecoder,
+    /// The received codeword.
+    codeword: &'a DuoBinaryCodeword<Llr>,
+    /// The interleaver of the couples.
+    interleaver: I,
+    /// The standard that defines which couples are switched.
+    standard: CtcStandard,
+    /// The number of completed iterations.
+    iteration: usize,
+}
+
+/// Scale the extrinsic symbol log-likelihoods in place.
+fn scale_symbols(scale: ExtrinsicScale, symbols: &mut [SymbolLlr]) {
+    if scale == ExtrinsicScale::ONE {
+        return;
+    }
+
+    for symbol in symbols.iter_mut() {
+        symbol.0 = scale.scale(DWord::new_i8h(symbol.0)).i8h();
+    }
+}
+
+impl DuoBinaryTurboDecoder {
+    /// Create a new duo-binary Turbo decoder using the `bcjr` constituent decoder.
+    pub fn new(bcjr: DuoBinaryTrellis) -> Self {
+        Self {
+            bcjr,
+            la_first: vec![],
+            l_app_deinterleaved: vec![],
+            extrinsic_scale: vec![],
+            first_circular: CircularMetrics::new(),
+            second_circular: CircularMetrics::new(),
+        }
+    }
+
+    /// Scale the extrinsic information exchanged between the constituent decoders.
+    /// The factor `per_iteration[i]` is used in iteration `i`, and the last factor is used for all following iterations.
+    pub fn with_extrinsic_scale(mut self, per_iteration: &[ExtrinsicScale]) -> Self {
+        self.extrinsic_scale = per_iteration.to_vec();
+        self
+    }
+
+    /// Produce an iterator for the duo-binary Turbo decoder from a received `codeword`.
+    /// Each iteration in the iterator corresponds to a Turbo decoder iteration,
+    /// and produces the a-posteriori symbol log-likelihoods of the couples.
+    /// The `interleaver` permutes the couples, and the bits of the couples are switched as by the `standard`,
+    /// e.g. `WimaxCtc::get(n)` with `CtcStandard::Wimax`.
+    pub fn decode<'a, I: Interleaver>(
+        &'a mut self,
+        codeword: &'a DuoBinaryCodeword<Llr>,
+        interleaver: I,
+        standard: CtcStandard,
+    ) -> DuoBinaryTurboDecodeIterator<'a, I> {
+        let n = codeword.len();
+        assert_eq!(n, interleaver.len());
+        for part in [
+            &codeword.b,
+            &codeword.first_y,
+            &codeword.first_w,
+            &codeword.second_y,
+            &codeword.second_w,
+        ] {
+            assert_eq!(n, part.len());
+        }
+
+        DuoBinaryTurboDecodeIterator {
+            decoder: self,
+            codeword,
+            interleaver,
+            standard,
+            iteration: 0,
+        }
+    }
+}
+
+impl<'a, I: Interleaver> Drop for DuoBinaryTurboDecodeIterator<'a, I> {
+    fn drop(&mut self) {
+        self.decoder.la_first.clear();
+        self.decoder.l_app_deinterleaved.clear();
+        self.decoder.first_circular.clear();
+        self.decoder.second_circular.clear();
+    }
+}
+
+impl<'a, I: Interleaver> StreamingIterator for DuoBinaryTurboDecodeIterator<'a, I> {
+    type Item = [SymbolLlr];
+
+    fn advance(&mut self) {
+        let codeword = self.codeword;
+        let interleaver = &self.interleaver;
+        let standard = self.standard;
+        let n = codeword.len();
+
+        let extrinsic_scale = self
+            .decoder
+            .extrinsic_scale
+            .get(self.iteration)
+            .or_else(|| self.decoder.extrinsic_scale.last())
+            .copied()
+            .unwrap_or(ExtrinsicScale::ONE);
+
+        // The a-priori symbol log-likelihoods are equiprobable in the first iteration.
+        if self.decoder.la_first.is_empty() {
+            self.decoder.la_first = vec![SymbolLlr::ZERO; n];
+        }
+        let la_first = &self.decoder.la_first;
+
+        let systematic_first: Vec<(Llr, Llr)> = codeword
+            .a
+            .iter()
+            .copied()
+            .zip(codeword.b.iter().copied())
+            .collect();
+
+        // The couples in the interleaved order, with the bits switched as by the interleaver.
+        let systematic_second: Vec<(Llr, Llr)> = interleaver
+            .iter()
+            .map(|i| {
+                let (a, b) = systematic_first[i];
+                if standard.switched(i) {
+                    (b, a)
+                } else {
+                    (a, b)
+                }
+            })
+            .collect();
+
+        // Run the BCJR algorithm and compute the a-posteriori symbol log-likelihoods for the first decoder.
+        let l_app_first = self.decoder.bcjr.decode(
+            systematic_first.iter().copied(),
+            codeword
+                .first_y
+                .iter()
+                .copied()
+                .zip(codeword.first_w.iter().copied()),
+            la_first.iter().copied(),
+            &mut self.decoder.first_circular,
+        );
+
+        // Compute the extrinsic information from the first decoder, to be used as the a-priori information for the second.
+        let mut la_second: Vec<SymbolLlr> = interleaver
+            .iter()
+            .map(|i| {
+                let (a, b) = systematic_first[i];
+                let extrinsic = l_app_first[i]
+                    .saturating_sub(la_first[i])
+                    .saturating_sub(SymbolLlr::from_bits(a, b));
+                if standard.switched(i) {
+                    extrinsic.swapped()
+                } else {
+                    extrinsic
+                }
+            })
+            .collect();
+        scale_symbols(extrinsic_scale, &mut la_second);
+
+        // Compute the a-posteriori symbol log-likelihoods for the second decoder.
+        let l_app_second = self.decoder.bcjr.decode(
+            systematic_second.iter().copied(),
+            codeword
+                .second_y
+                .iter()
+                .copied()
+                .zip(codeword.second_w.iter().copied()),
+            la_second.iter().copied(),
+            &mut self.decoder.second_circular,
+        );
+
+        // De-interleave the extrinsic information for the first decoder, and the a-posteriori values for decision making.
+        let mut la_first = vec![SymbolLlr::ZERO; n];
+        let mut l_app = vec![SymbolLlr::ZERO; n];
+        for (j, i) in interleaver.iter().enumerate() {
+            let (a, b) = systematic_second[j];
+            let extrinsic = l_app_second[j]
+                .saturating_sub(la_second[j])
+                .saturating_sub(SymbolLlr::from_bits(a, b));
+            if standard.switched(i) {
+                la_first[i] = extrinsic.swapped();
+                l_app[i] = l_app_second[j].swapped();
+            } else {
+                la_first[i] = extrinsic;
+                l_app[i] = l_app_second[j];
+            }
+        }
+        scale_symbols(extrinsic_scale, &mut la_first);
+
+        self.decoder.la_first = la_first;
+        self.decoder.l_app_deinterleaved = l_app;
+        self.iteration += 1;
+    }
+
+    fn get(&self) -> Option<&Self::Item> {
+        Some(&self.decoder.l_app_deinterleaved)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        encoders::duobinary::DuoBinaryTurboEncoder,
+        interleavers::ctc::{CtcInterleaver, DvbRcsCtc, WimaxCtc},
+    };
+
+    fn llrs(bits: &[bool]) -> Vec<Llr> {
+        bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+    }
+
+    #[test]
+    fn encode_decode() {
+        let interleavers: Vec<CtcInterleaver> = [24, 96, 240, 480]
+            .iter()
+            .map(|&n| WimaxCtc::get(n).unwrap())
+            .chain([48, 212, 752].iter().map(|&n| DvbRcsCtc::get(n).unwrap()))
+            .collect();
+        for interleaver in interleavers {
+            let n = interleaver.n();
+            let standard = interleaver.standard();
+            let input: Vec<bool> = (0..2 * n).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+            let codeword = DuoBinaryTurboEncoder.encode(&input, &interleaver, standard);
+
+            let mut received = DuoBinaryCodeword {
+                a: llrs(&codeword.a),
+                b: llrs(&codeword.b),
+                first_y: llrs(&codeword.first_y),
+                first_w: llrs(&codeword.first_w),
+                second_y: llrs(&codeword.second_y),
+                second_w: llrs(&codeword.second_w),
+            };
+            for i in (0..n).step_by(5) {
+                received.a[i] = Llr(-received.a[i].0);
+                received.second_w[(i + 2) % n] = Llr::ZERO;
+            }
+
+            let mut turbo = DuoBinaryTurboDecoder::new(DuoBinaryTrellis::new(32))
+                .with_extrinsic_scale(&[ExtrinsicScale::from_sixteenths(12)]);
+            let mut iterator = turbo.decode(&received, &interleaver, standard);
+            let l_app = iterator.nth(3).unwrap();
+            let decoded: Vec<bool> = l_app
+                .iter()
+                .flat_map(|x| {
+                    let (a, b) = x.hard();
+                    [a, b]
+                })
+                .collect();
+            assert_eq!(input, decoded);
+
+            drop(iterator);
+            assert!(turbo.la_first.is_empty());
+            assert!(turbo.first_circular.is_empty());
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! CCSDS Turbo Encoder
+use crate::{trellises::generic::Trellis, Llr, TurboCodeword};
+use alloc::vec::Vec;
+
+/// The number of steps required to terminate the trellis of a constituent encoder.
+pub const TAIL_LEN: usize = 4;
+
+/// The number of parity outputs of a constituent encoder.
+pub const PARITY_OUTPUTS: usize = 3;
+
+/// The outputs of the two constituent encoders, named as in CCSDS 131.0-B 6.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Output {
+    /// The systematic output of the first encoder.
+    A0,
+    /// The parity outputs of the first encoder.
+    A1,
+    A2,
+    A3,
+    /// The parity outputs of the second encoder.
+    B1,
+    B3,
+}
+
+/// The nominal code rates of the CCSDS Turbo code, with the symbols transmitted for each step of the encoders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CcsdsRate {
+    /// `0a` followed by `1a` for even steps and `1b` for odd steps.
+    Half,
+    /// `0a, 1a, 1b`.
+    Third,
+    /// `0a, 2a, 3a, 1b`.
+    Quarter,
+    /// `0a, 1a, 2a, 3a, 1b, 3b`.
+    Sixth,
+}
+
+impl CcsdsRate {
+    /// The number of symbols transmitted for each step.
+    pub const fn symbols_per_step(&self) -> usize {
+        match self {
+            CcsdsRate::Half => 2,
+            CcsdsRate::Third => 3,
+            CcsdsRate::Quarter => 4,
+            CcsdsRate::Sixth => 6,
+        }
+    }
+
+    /// The number of transmitted symbols for an information block of `k` bits, including termination.
+    pub const fn codeword_len(&self, k: usize) -> usize {
+        (k + TAIL_LEN) * self.symbols_per_step()
+    }
+
+    /// The outputs transmitted at `step`, in transmission order.
+    fn outputs(&self, step: usize) -> &'static [Output] {
+        use Output::*;
+        match self {
+            CcsdsRate::Half if step.is_multiple_of(2) => &[A0, A1],
+            CcsdsRate::Half => &[A0, B1],
+            CcsdsRate::Third => &[A0, A1, B1],
+            CcsdsRate::Quarter => &[A0, A2, A3, B1],
+            CcsdsRate::Sixth => &[A0, A1, A2, A3, B1, B3],
+        }
+    }
+
+    /// Split the received `llrs` of a codeword into the parts consumed by the Turbo decoder,
+    /// which decodes the constituent code with all three parity outputs, see `ccsds_trellis()`.
+    /// The parity parts hold the `1`, `2` and `3` outputs of each step, where the punctured outputs are zero,
+    /// and so are `2b` and the systematic termination of the second encoder, as they are never transmitted.
+    pub fn depuncture(&self, llrs: &[Llr]) -> TurboCodeword<Llr> {
+        let n = self.symbols_per_step();
+        assert_eq!(0, llrs.len() % n);
+        let steps = llrs.len() / n;
+        assert!(steps >= TAIL_LEN);
+        let k = steps - TAIL_LEN;
+
+        let mut systematic = vec![Llr::ZERO; steps];
+        let mut first_parity = vec![Llr::ZERO; PARITY_OUTPUTS * steps];
+        let mut second_parity = vec![Llr::ZERO; PARITY_OUTPUTS * steps];
+
+        for (step, symbols) in llrs.chunks_exact(n).enumerate() {
+            let parity = PARITY_OUTPUTS * step;
+            for (&output, &llr) in self.outputs(step).iter().zip(symbols) {
+                match output {
+                    Output::A0 => systematic[step] = llr,
+                    Output::A1 => first_parity[parity] = llr,
+                    Output::A2 => first_parity[parity + 1] = llr,
+                    Output::A3 => first_parity[parity + 2] = llr,
+                    Output::B1 => second_parity[parity] = llr,
+                    Output::B3 => second_parity[parity + 2] = llr,
+                }
+            }
+        }
+
+        let first_systematic_termination = systematic.split_off(k);
+        TurboCodeword {
+            systematic,
+            first_systematic_termination,
+            first_parity,
+            second_systematic_termination: vec![Llr::ZERO; TAIL_LEN],
+            second_parity,
+        }
+    }
+}
+
+/// The trellis of the constituent code, with the systematic output followed by the `1`, `2` and `3` parity outputs.
+/// A Turbo decoder for all the rates uses `ScalarBcjr::new(ccsds_trellis())` for the codewords from `depuncture()`.
+pub fn ccsds_trellis() -> Trellis {
+    Trellis::recursive(5, 0o23, &[0o33, 0o25, 0o37])
+}
+
+/// CCSDS Turbo encoder (PCCC), see CCSDS 131.0-B 6.
+/// The constituent encoders are 16-state recursive systematic encoders with the feedback `1 + D^3 + D^4` (23 octal)
+/// and the parity outputs `1 + D + D^3 + D^4` (33 octal), `1 + D^2 + D^4` (25 octal) and `1 + D + D^2 + D^3 + D^4` (37 octal).
+/// Both encoders are terminated at the same time, where the systematic output of the first encoder
+/// carries its termination input, and the termination input of the second encoder is not transmitted.
+pub struct CcsdsTurboEncoder {
+    rate: CcsdsRate,
+    trellis: Trellis,
+}
+
+impl CcsdsTurboEncoder {
+    pub fn new(rate: CcsdsRate) -> Self {
+        Self {
+            rate,
+            trellis: ccsds_trellis(),
+        }
+    }
+
+    pub const fn rate(&self) -> CcsdsRate {
+        self.rate
+    }
+
+    /// Encode a block of `input` bits using the `permutation` for the second encoder,
+    /// and return the transmitted symbols.
+    pub fn encode<I: IntoIterator<Item = usize>>(
+        &self,
+        input: &[bool],
+        permutation: I,
+    ) -> Vec<bool> {
+        let first = self.trellis.encode(input, true);
+        let permuted: Vec<bool> = permutation.into_iter().map(|index| input[index]).collect();
+        assert_eq!(input.len(), permuted.len());
+        let second = self.trellis.encode(&permuted, true);
+
+        let steps = input.len() + TAIL_LEN;
+        let mut symbols = Vec::with_capacity(self.rate.codeword_len(input.len()));
+        for step in 0..steps {
+            for output in self.rate.outputs(step) {
+                symbols.push(match output {
+                    Output::A0 => first[0][step],
+                    Output::A1 => first[1][step],
+                    Output::A2 => first[2][step],
+                    Output::A3 => first[3][step],
+                    Output::B1 => second[1][step],
+                    Output::B3 => second[3][step],
+                });
+            }
+        }
+        symbols
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        interleavers::ccsds::CcsdsPermutation,
+        trellises::{generic::ScalarBcjr, packed::PackedTrellis},
+        TurboDecoder,
+    };
+    use streaming_iterator::StreamingIterator;
+
+    const RATES: [CcsdsRate; 4] = [
+        CcsdsRate::Half,
+        CcsdsRate::Third,
+        CcsdsRate::Quarter,
+        CcsdsRate::Sixth,
+    ];
+
+    fn input(k: usize) -> Vec<bool> {
+        (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect()
+    }
+
+    fn llrs(bits: &[bool]) -> Vec<Llr> {
+        bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+    }
+
+    #[test]
+    fn constituent_code() {
+        let trellis = ccsds_trellis();
+        assert_eq!(16, trellis.states());
+
+        // The impulse response of 1a, (1 + D + D^3 + D^4) / (1 + D^3 + D^4) = 1 + D + D^4 + D^5 + ...
+        let streams = trellis.encode(&[true, false, false, false, false, false], false);
+        assert_eq!(vec![true, true, false, false, true, true], streams[1]);
+    }
+
+    #[test]
+    fn encode() {
+        let k = 1784;
+        let input = input(k);
+        let permutation = CcsdsPermutation::get(k).unwrap();
+        let sixth = CcsdsTurboEncoder::new(CcsdsRate::Sixth).encode(&input, permutation.clone());
+        assert_eq!(CcsdsRate::Sixth.codeword_len(k), sixth.len());
+
+        // The other rates transmit subsets of the rate 1/6 outputs.
+        let select: [(CcsdsRate, &[usize], &[usize]); 3] = [
+            (CcsdsRate::Half, &[0, 1], &[0, 4]),
+            (CcsdsRate::Third, &[0, 1, 4], &[0, 1, 4]),
+            (CcsdsRate::Quarter, &[0, 2, 3, 4], &[0, 2, 3, 4]),
+        ];
+        for &(rate, even, odd) in select.iter() {
+            let symbols = CcsdsTurboEncoder::new(rate).encode(&input, permutation.clone());
+            let expected: Vec<bool> = sixth
+                .chunks_exact(6)
+                .enumerate()
+                .flat_map(|(step, outputs)| {
+                    let indices = if step.is_multiple_of(2) { even } else { odd };
+                    indices.iter().map(move |&i| outputs[i])
+                })
+                .collect();
+            assert_eq!(expected, symbols);
+        }
+
+        // The systematic output is the input followed by the termination.
+        let systematic: Vec<bool> = sixth.iter().step_by(6).copied().collect();
+        assert_eq!(input[..], systematic[..k]);
+    }
+
+    #[test]
+    fn depuncture() {
+        let llrs: Vec<Llr> = (0..2 * 8).map(|i| Llr(i as i8)).collect();
+        let codeword = CcsdsRate::Half.depuncture(&llrs);
+        assert_eq!(crate::llr_vec![0, 2, 4, 6], codeword.systematic);
+        assert_eq!(
+            crate::llr_vec![8, 10, 12, 14],
+            codeword.first_systematic_termination
+        );
+        assert_eq!(
+            crate::llr_vec![1, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0],
+            codeword.first_parity[..12]
+        );
+        assert_eq!(
+            crate::llr_vec![0, 0, 0, 3, 0, 0, 0, 0, 0, 7, 0, 0],
+            codeword.second_parity[..12]
+        );
+        assert_eq!(
+            vec![Llr::ZERO; TAIL_LEN],
+            codeword.second_systematic_termination
+        );
+
+        let llrs: Vec<Llr> = (0..6 * 5).map(|i| Llr(i as i8)).collect();
+        let codeword = CcsdsRate::Sixth.depuncture(&llrs);
+        assert_eq!(crate::llr_vec![0], codeword.systematic);
+        assert_eq!(crate::llr_vec![1, 2, 3], codeword.first_parity[..3]);
+        assert_eq!(crate::llr_vec![4, 0, 5], codeword.second_parity[..3]);
+
+        let llrs: Vec<Llr> = (0..4 * 5).map(|i| Llr(i as i8)).collect();
+        let codeword = CcsdsRate::Quarter.depuncture(&llrs);
+        assert_eq!(crate::llr_vec![0, 1, 2], codeword.first_parity[..3]);
+        assert_eq!(crate::llr_vec![3, 0, 0], codeword.second_parity[..3]);
+    }
+
+    /// Encode, flip every `spacing`'th transmitted symbol, and decode.
+    fn encode_decode_impl(rate: CcsdsRate, spacing: usize) -> bool {
+        let k = 1784;
+        let input = input(k);
+        let permutation = CcsdsPermutation::get(k).unwrap();
+
+        let symbols = CcsdsTurboEncoder::new(rate).encode(&input, permutation.clone());
+        let mut received = llrs(&symbols);
+        for i in (0..received.len()).step_by(spacing) {
+            received[i] = Llr(-received[i].0);
+        }
+
+        let codeword = rate.depuncture(&received);
+        let mut turbo = TurboDecoder::new(ScalarBcjr::new(ccsds_trellis()));
+        let mut iterator = turbo.decode_codeword(&codeword, permutation);
+        let l_app = iterator.nth(3).unwrap();
+        l_app.iter().map(|x| x.hard()).eq(input.iter().copied())
+    }
+
+    #[test]
+    fn encode_decode() {
+        for &rate in RATES.iter() {
+            assert!(encode_decode_impl(rate, 23), "{:?}", rate);
+        }
+    }
+
+    #[test]
+    fn encode_decode_low_rate() {
+        // The extra parity outputs of the low rates correct more errors.
+        assert!(!encode_decode_impl(CcsdsRate::Third, 5));
+        assert!(encode_decode_impl(CcsdsRate::Quarter, 5));
+        assert!(encode_decode_impl(CcsdsRate::Sixth, 5));
+    }
+
+    #[test]
+    fn packed_decoder() {
+        // The packed decoder uses only the `1a` and `1b` outputs.
+        let k = 1784;
+        let input = input(k);
+        let permutation = CcsdsPermutation::get(k).unwrap();
+        let symbols = CcsdsTurboEncoder::new(CcsdsRate::Third).encode(&input, permutation.clone());
+        let codeword = CcsdsRate::Third.depuncture(&llrs(&symbols));
+        let first_parity: Vec<Llr> = codeword.first_parity.iter().step_by(3).copied().collect();
+        let second_parity: Vec<Llr> = codeword.second_parity.iter().step_by(3).copied().collect();
+
+        let mut turbo = TurboDecoder::new(PackedTrellis::CCSDS);
+        let mut iterator = turbo.decode(
+            &codeword.systematic,
+            Some(&codeword.first_systematic_termination),
+            &first_parity,
+            Some(&codeword.second_systematic_termination),
+            &second_parity,
+            permutation,
+        );
+        let l_app = iterator.nth(1).unwrap();
+        assert!(l_app.iter().map(|x| x.hard()).eq(input.iter().copied()));
+    }
+}
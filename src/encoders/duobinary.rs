@@ -0,0 +1,112 @@
+//! Duo-Binary Circular Turbo Encoder
+use crate::{
+    interleavers::{ctc::CtcStandard, Interleaver},
+    trellises::duobinary::{circulation_state, step},
+    DuoBinaryCodeword,
+};
+use alloc::vec::Vec;
+
+/// Duo-binary circular Turbo encoder of DVB-RCS and 802.16 (CTC), see 802.16 8.4.9.2.3 and EN 301 790.
+/// It uses the same constituent code as the `DuoBinaryTrellis` decoder.
+pub struct DuoBinaryTurboEncoder;
+
+/// Encode `couples` with the constituent encoder started in the circulation state,
+/// and return the `(Y, W)` parity bits.
+fn encode_circular<C: Iterator<Item = (bool, bool)> + Clone>(couples: C) -> (Vec<bool>, Vec<bool>) {
+    // The first pass finds the state reached from the all-zero state.
+    let (n, final_state) = couples
+        .clone()
+        .fold((0, 0), |(n, state), (a, b)| (n + 1, step(state, a, b).0));
+
+    let start = circulation_state(n, final_state);
+    let mut state = start;
+    let mut y = Vec::with_capacity(n);
+    let mut w = Vec::with_capacity(n);
+    for (a, b) in couples {
+        let (next, y_bit, w_bit) = step(state, a, b);
+        y.push(y_bit);
+        w.push(w_bit);
+        state = next;
+    }
+
+    debug_assert_eq!(start, state);
+    (y, w)
+}
+
+impl DuoBinaryTurboEncoder {
+    /// Encode a block of `input` bits, where bits `2j` and `2j + 1` are the bits `A` and `B` of couple `j`,
+    /// using the `interleaver` of the couples for the second encoder, with the bits switched as by the `standard`.
+    pub fn encode<I: Interleaver>(
+        &self,
+        input: &[bool],
+        interleaver: I,
+        standard: CtcStandard,
+    ) -> DuoBinaryCodeword<bool> {
+        assert_eq!(2 * interleaver.len(), input.len());
+        let a: Vec<bool> = input.iter().step_by(2).copied().collect();
+        let b: Vec<bool> = input.iter().skip(1).step_by(2).copied().collect();
+
+        let (first_y, first_w) = encode_circular(a.iter().copied().zip(b.iter().copied()));
+        let interleaved: Vec<(bool, bool)> = interleaver
+            .iter()
+            .map(|i| {
+                if standard.switched(i) {
+                    (b[i], a[i])
+                } else {
+                    (a[i], b[i])
+                }
+            })
+            .collect();
+        let (second_y, second_w) = encode_circular(interleaved.iter().copied());
+
+        DuoBinaryCodeword {
+            a,
+            b,
+            first_y,
+            first_w,
+            second_y,
+            second_w,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::interleavers::ctc::{CtcInterleaver, DvbRcsCtc, WimaxCtc};
+
+    #[test]
+    fn encode() {
+        let n = 24;
+        let input: Vec<bool> = (0..2 * n).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let interleaver = WimaxCtc::get(n).unwrap();
+        let codeword = DuoBinaryTurboEncoder.encode(&input, &interleaver, CtcStandard::Wimax);
+
+        assert_eq!(n, codeword.len());
+        assert_eq!(input[0], codeword.a[0]);
+        assert_eq!(input[1], codeword.b[0]);
+        assert_eq!(input[46], codeword.a[23]);
+        assert_eq!(n, codeword.second_w.len());
+
+        // A block of zeros is encoded into zeros.
+        let zeros = DuoBinaryTurboEncoder.encode(&[false; 48], &interleaver, CtcStandard::Wimax);
+        assert!(zeros.first_y.iter().chain(&zeros.second_w).all(|&x| !x));
+    }
+
+    #[test]
+    fn switched_couples() {
+        // The second encoder of DVB-RCS sees the couples of an 802.16 encoder with the same permutation,
+        // with the bits of all the couples switched.
+        let n = 48;
+        let input: Vec<bool> = (0..2 * n).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let switched: Vec<bool> = input.chunks(2).flat_map(|x| [x[1], x[0]]).collect();
+        let dvb_rcs = DvbRcsCtc::get(n).unwrap();
+        let wimax = CtcInterleaver::new(CtcStandard::Wimax, n, 11, 24, 0, 24);
+
+        let first = DuoBinaryTurboEncoder.encode(&input, &dvb_rcs, CtcStandard::DvbRcs);
+        let second = DuoBinaryTurboEncoder.encode(&switched, &wimax, CtcStandard::Wimax);
+        assert_eq!(first.second_y, second.second_y);
+        assert_eq!(first.second_w, second.second_w);
+        assert_ne!(first.first_y, second.first_y);
+    }
+}
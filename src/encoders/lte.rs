@@ -0,0 +1,121 @@
+//! LTE Turbo Encoder
+use super::rsc::{RscEncoder, TAIL_LEN};
+use crate::TurboCodeword;
+
+/// LTE Turbo encoder (PCCC), see 36.212 5.1.3.2.
+/// It uses the same constituent code as the `UmtsTrellis` decoder.
+pub struct LteTurboEncoder;
+
+impl LteTurboEncoder {
+    /// Encode a block of `input` bits using the `interleaver` for the second encoder.
+    /// The produced codeword has the same layout as expected by `TurboDecoder::decode`.
+    pub fn encode<I: IntoIterator<Item = usize>>(
+        &self,
+        input: &[bool],
+        interleaver: I,
+    ) -> TurboCodeword<bool> {
+        let (first_parity, first_systematic_termination) =
+            RscEncoder::new().encode(input.iter().copied(), true);
+        let (second_parity, second_systematic_termination) = RscEncoder::new().encode(
+            interleaver.into_iter().map(|int_index| input[int_index]),
+            true,
+        );
+
+        assert_eq!(input.len() + TAIL_LEN, second_parity.len());
+
+        TurboCodeword {
+            systematic: input.to_vec(),
+            first_systematic_termination,
+            first_parity,
+            second_systematic_termination,
+            second_parity,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        interleavers::{lte::LteQpp, qpp::Qpp},
+        trellises::lte::UmtsTrellis,
+        Llr, TurboDecoder,
+    };
+    use alloc::vec::Vec;
+    use streaming_iterator::StreamingIterator;
+
+    fn bits(llrs: &[i8]) -> Vec<bool> {
+        llrs.iter().map(|&x| Llr(x).hard()).collect()
+    }
+
+    fn llrs(bits: &[bool]) -> Vec<Llr> {
+        bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+    }
+
+    #[test]
+    fn encode_excel_example() {
+        let input = bits(&[-4, -4, -4, 4, -4, -4, 4, 4, -4, -4, -4, -4, -4, -4, 4, -4]);
+
+        let codeword = LteTurboEncoder.encode(&input, Qpp::new(16, 1, 4));
+
+        assert_eq!(input, codeword.systematic);
+        assert_eq!(bits(&[4, -4, 4]), codeword.first_systematic_termination);
+        assert_eq!(
+            bits(&[-4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, 4]),
+            codeword.first_parity
+        );
+        assert_eq!(bits(&[-4, -4, -4]), codeword.second_systematic_termination);
+        assert_eq!(
+            bits(&[-4, -4, -4, 4, 4, 4, -4, 4, 4, -4, -4, 4, -4, 4, -4, 4, -4, -4, -4]),
+            codeword.second_parity
+        );
+    }
+
+    #[test]
+    fn streams() {
+        let input = bits(&[-4, -4, -4, 4, -4, -4, 4, 4, -4, -4, -4, -4, -4, -4, 4, -4]);
+
+        let codeword = LteTurboEncoder.encode(&input, Qpp::new(16, 1, 4));
+        let d0 = codeword.d0();
+        let d1 = codeword.d1();
+        let d2 = codeword.d2();
+
+        assert_eq!(input[..], d0[..16]);
+        assert_eq!(codeword.first_parity[..16], d1[..16]);
+        assert_eq!(codeword.second_parity[..16], d2[..16]);
+
+        // The 12 tail bits, x_K, z_K+1, x'_K, z'_K+1 etc.
+        assert_eq!(bits(&[4, 4, -4, -4]), d0[16..]);
+        assert_eq!(bits(&[4, 4, -4, -4]), d1[16..]);
+        assert_eq!(bits(&[-4, 4, -4, -4]), d2[16..]);
+    }
+
+    #[test]
+    fn encode_decode() {
+        let k = 40;
+        let input: Vec<bool> = (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let interleaver = LteQpp::get(k).unwrap();
+
+        let codeword = LteTurboEncoder.encode(&input, interleaver.clone());
+
+        let systematic = llrs(&codeword.systematic);
+        let first_systematic_termination = llrs(&codeword.first_systematic_termination);
+        let first_parity = llrs(&codeword.first_parity);
+        let second_systematic_termination = llrs(&codeword.second_systematic_termination);
+        let second_parity = llrs(&codeword.second_parity);
+
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = turbo.decode(
+            &systematic,
+            Some(&first_systematic_termination),
+            &first_parity,
+            Some(&second_systematic_termination),
+            &second_parity,
+            interleaver,
+        );
+
+        let l_app = iterator.next().unwrap();
+        let decoded: Vec<bool> = l_app.iter().map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+    }
+}
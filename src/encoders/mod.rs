@@ -0,0 +1,4 @@
+pub mod ccsds;
+pub mod duobinary;
+pub mod lte;
+pub mod rsc;
@@ -0,0 +1,173 @@
+//! Recursive Systematic Convolutional Encoder
+use alloc::vec::Vec;
+
+/// 8-state Recursive Systematic Convolutional (RSC) encoder with transfer function `[1, g1(D)/g0(D)]`,
+/// where `g0(D) = 1 + D^2 + D^3` (13 octal) and `g1(D) = 1 + D + D^3` (15 octal).
+/// This is the constituent code of the 3GPP Turbo code, and the code decoded by `UmtsTrellis`.
+/// The state is numbered such that the most recent register bit is the MSB,
+/// i.e. the same numbering as used for the states in `UmtsTrellis`.
+#[derive(Clone, Debug, Default)]
+pub struct RscEncoder {
+    state: u8,
+}
+
+/// The number of tail bits required to terminate the trellis.
+pub const TAIL_LEN: usize = 3;
+
+impl RscEncoder {
+    /// Create a new encoder in the all-zero state.
+    pub const fn new() -> Self {
+        Self { state: 0 }
+    }
+
+    /// Get the current encoder state.
+    pub const fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// Encode a single input bit and return the emitted parity bit.
+    pub fn encode_bit(&mut self, u: bool) -> bool {
+        let s = self.state;
+        let feedback = ((s >> 1) ^ s) & 1 == 1;
+        let a = u ^ feedback;
+        let parity = a ^ (((s >> 2) ^ s) & 1 == 1);
+        self.state = ((a as u8) << 2) | (s >> 1);
+        parity
+    }
+
+    /// Get the input bit that moves the encoder towards the all-zero state.
+    pub const fn termination_bit(&self) -> bool {
+        let s = self.state;
+        ((s >> 1) ^ s) & 1 == 1
+    }
+
+    /// Terminate the trellis by appending the tail bits that drive the encoder to the all-zero state.
+    /// Returns the `(systematic, parity)` pairs that were emitted.
+    pub fn terminate(&mut self) -> [(bool, bool); TAIL_LEN] {
+        let mut tail = [(false, false); TAIL_LEN];
+        for pair in tail.iter_mut() {
+            let u = self.termination_bit();
+            *pair = (u, self.encode_bit(u));
+        }
+        debug_assert_eq!(0, self.state);
+        tail
+    }
+
+    /// Encode a block of `input` bits, optionally followed by trellis termination.
+    /// Returns the parity (including termination parity if `terminated`) and the systematic termination,
+    /// which is empty if the block is not `terminated`.
+    pub fn encode<U: IntoIterator<Item = bool>>(
+        &mut self,
+        input: U,
+        terminated: bool,
+    ) -> (Vec<bool>, Vec<bool>) {
+        let input = input.into_iter();
+        let mut parity = Vec::with_capacity(input.size_hint().0 + TAIL_LEN);
+        let mut termination = Vec::with_capacity(TAIL_LEN);
+
+        for u in input {
+            parity.push(self.encode_bit(u));
+        }
+
+        if terminated {
+            for (u, v) in self.terminate().iter().copied() {
+                termination.push(u);
+                parity.push(v);
+            }
+        }
+
+        (parity, termination)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{trellises::lte::UmtsTrellis, BcjrDecoder, Llr};
+
+    fn llrs(bits: &[bool]) -> Vec<Llr> {
+        bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+    }
+
+    #[test]
+    fn transitions() {
+        // (state, input) -> (next state, parity), as listed in `UmtsTrellis`.
+        let transitions = [
+            (0, false, 0, false),
+            (0, true, 4, true),
+            (1, false, 4, false),
+            (1, true, 0, true),
+            (2, false, 5, true),
+            (2, true, 1, false),
+            (3, false, 1, true),
+            (3, true, 5, false),
+            (4, false, 2, true),
+            (4, true, 6, false),
+            (5, false, 6, true),
+            (5, true, 2, false),
+            (6, false, 7, false),
+            (6, true, 3, true),
+            (7, false, 3, false),
+            (7, true, 7, true),
+        ];
+
+        for &(state, u, next, v) in transitions.iter() {
+            let mut encoder = RscEncoder { state };
+            assert_eq!(v, encoder.encode_bit(u));
+            assert_eq!(next, encoder.state());
+        }
+    }
+
+    #[test]
+    fn terminate_from_any_state() {
+        for state in 0..8 {
+            let mut encoder = RscEncoder { state };
+            encoder.terminate();
+            assert_eq!(0, encoder.state());
+        }
+    }
+
+    #[test]
+    fn encode_terminated() {
+        let input = [true, true, false, true, true, false, false, true];
+
+        let (parity, termination) = RscEncoder::new().encode(input.iter().copied(), true);
+
+        assert_eq!(
+            vec![true, false, false, true, true, false, true, true, false, false, false],
+            parity
+        );
+        assert_eq!(vec![false, false, false], termination);
+
+        let systematic: Vec<bool> = input.iter().chain(&termination).copied().collect();
+        let l_app = UmtsTrellis.decode(
+            llrs(&systematic).into_iter(),
+            llrs(&parity).into_iter(),
+            core::iter::repeat_n(Llr::ZERO, systematic.len()),
+            true,
+        );
+        let decoded: Vec<bool> = l_app.iter().map(|x| x.hard()).collect();
+        assert_eq!(systematic, decoded);
+    }
+
+    #[test]
+    fn encode_unterminated() {
+        let input: Vec<bool> = (0..40).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+
+        let mut encoder = RscEncoder::new();
+        let (parity, termination) = encoder.encode(input.iter().copied(), false);
+
+        assert_eq!(input.len(), parity.len());
+        assert!(termination.is_empty());
+        assert_ne!(0, encoder.state());
+
+        let l_app = UmtsTrellis.decode(
+            llrs(&input).into_iter(),
+            llrs(&parity).into_iter(),
+            core::iter::repeat_n(Llr::ZERO, input.len()),
+            false,
+        );
+        let decoded: Vec<bool> = l_app.iter().map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+    }
+}
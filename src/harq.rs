@@ -0,0 +1,229 @@
+//! HARQ Soft Buffer Combining
+use crate::{
+    interleavers::Interleaver, rate_matching::RateMatcher, turbo::TurboDecodeIterator, BcjrDecoder,
+    Llr, TurboCodeword, TurboDecoder,
+};
+use alloc::collections::BTreeMap;
+
+/// Soft buffer for a single code block.
+/// De-rate-matched soft values are accumulated across the transmissions of the block,
+/// both for chase combining (same redundancy version) and incremental redundancy (different redundancy versions).
+pub struct SoftBuffer {
+    /// The rate matcher used for the block.
+    rate_matcher: RateMatcher,
+    /// The combined soft values.
+    codeword: TurboCodeword<Llr>,
+    /// The number of combined transmissions.
+    transmissions: usize,
+}
+
+impl SoftBuffer {
+    /// Create an empty soft buffer for the block length of the rate matcher.
+    pub fn new(rate_matcher: RateMatcher) -> Self {
+        Self {
+            rate_matcher,
+            codeword: TurboCodeword::from_elem(rate_matcher.k(), Llr::ZERO),
+            transmissions: 0,
+        }
+    }
+
+    /// The block length `K`.
+    pub fn len(&self) -> usize {
+        self.codeword.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codeword.is_empty()
+    }
+
+    /// The rate matcher used for the block.
+    pub fn rate_matcher(&self) -> &RateMatcher {
+        &self.rate_matcher
+    }
+
+    /// The number of transmissions combined into the buffer.
+    pub fn transmissions(&self) -> usize {
+        self.transmissions
+    }
+
+    /// Combine the `received` soft values for the redundancy version `rv` into the buffer.
+    /// The values are combined using saturating addition.
+    pub fn combine(&mut self, received: &[Llr], rv: u8) {
+        self.rate_matcher
+            .dematch_into(received, rv, &mut self.codeword);
+        self.transmissions += 1;
+    }
+
+    /// Get the combined soft values.
+    pub fn codeword(&self) -> &TurboCodeword<Llr> {
+        &self.codeword
+    }
+
+    /// Clear the buffer, e.g. when new data is scheduled for the HARQ process.
+    pub fn clear(&mut self) {
+        let k = self.codeword.len();
+        self.codeword = TurboCodeword::from_elem(k, Llr::ZERO);
+        self.transmissions = 0;
+    }
+
+    /// Produce a Turbo decoder iterator for the combined soft values.
+    pub fn decode<'a, B: BcjrDecoder, I: Interleaver>(
+        &'a self,
+        decoder: &'a mut TurboDecoder<B>,
+        interleaver: I,
+    ) -> TurboDecodeIterator<'a, B, I> {
+        decoder.decode_codeword(&self.codeword, interleaver)
+    }
+}
+
+/// Soft buffers keyed by HARQ process and code block index.
+#[derive(Default)]
+pub struct HarqBuffers {
+    buffers: BTreeMap<(u8, usize), SoftBuffer>,
+}
+
+impl HarqBuffers {
+    pub const fn new() -> Self {
+        Self {
+            buffers: BTreeMap::new(),
+        }
+    }
+
+    /// Get the soft buffer for a code block in a HARQ process.
+    pub fn get(&self, process: u8, code_block: usize) -> Option<&SoftBuffer> {
+        self.buffers.get(&(process, code_block))
+    }
+
+    /// Get the soft buffer for a code block in a HARQ process, creating it if it does not exist.
+    /// An existing buffer is replaced if it was created for a different rate matcher,
+    /// e.g. a different block length, circular buffer length or number of filler bits.
+    pub fn get_or_insert(
+        &mut self,
+        process: u8,
+        code_block: usize,
+        rate_matcher: RateMatcher,
+    ) -> &mut SoftBuffer {
+        let buffer = self
+            .buffers
+            .entry((process, code_block))
+            .or_insert_with(|| SoftBuffer::new(rate_matcher));
+
+        if buffer.rate_matcher != rate_matcher {
+            *buffer = SoftBuffer::new(rate_matcher);
+        }
+
+        buffer
+    }
+
+    /// Release all soft buffers for a HARQ process, e.g. when the transport block was successfully decoded.
+    pub fn release(&mut self, process: u8) {
+        self.buffers.retain(|&(p, _), _| p != process);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        encoders::lte::LteTurboEncoder, interleavers::lte::LteQpp, trellises::lte::UmtsTrellis,
+    };
+    use alloc::vec::Vec;
+    use streaming_iterator::StreamingIterator;
+
+    fn llrs(bits: &[bool]) -> Vec<Llr> {
+        bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+    }
+
+    fn count_received(codeword: &TurboCodeword<Llr>) -> usize {
+        [codeword.d0(), codeword.d1(), codeword.d2()]
+            .iter()
+            .flatten()
+            .filter(|&&x| x != Llr::ZERO)
+            .count()
+    }
+
+    #[test]
+    fn chase_combining() {
+        let k = 40;
+        let rate_matcher = RateMatcher::new(k);
+        let mut buffer = SoftBuffer::new(rate_matcher);
+
+        buffer.combine(&vec![Llr(100); 2 * k], 0);
+        buffer.combine(&vec![Llr(100); 2 * k], 0);
+
+        assert_eq!(2, buffer.transmissions());
+        assert_eq!(2 * k, count_received(buffer.codeword()));
+        assert!(buffer
+            .codeword()
+            .d0()
+            .iter()
+            .all(|&x| x == Llr::ZERO || x == Llr(127)));
+    }
+
+    #[test]
+    fn incremental_redundancy() {
+        let k = 40;
+        let input: Vec<bool> = (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let interleaver = LteQpp::get(k).unwrap();
+        let codeword = LteTurboEncoder.encode(&input, interleaver.clone());
+        let rate_matcher = RateMatcher::new(k);
+
+        let mut buffers = HarqBuffers::new();
+        for rv in [0, 2].iter().copied() {
+            let e = rate_matcher.rate_match(&codeword, k, rv);
+            buffers
+                .get_or_insert(3, 0, rate_matcher)
+                .combine(&llrs(&e), rv);
+        }
+
+        let buffer = buffers.get(3, 0).unwrap();
+        assert_eq!(2, buffer.transmissions());
+        assert!(count_received(buffer.codeword()) > k);
+
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = buffer.decode(&mut turbo, interleaver);
+        let l_app = iterator.nth(3).unwrap();
+        let decoded: Vec<bool> = l_app.iter().map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+        drop(iterator);
+
+        buffers.release(3);
+        assert!(buffers.get(3, 0).is_none());
+    }
+
+    #[test]
+    fn block_length_change_clears_buffer() {
+        let mut buffers = HarqBuffers::new();
+
+        let rate_matcher = RateMatcher::new(40);
+        buffers
+            .get_or_insert(1, 0, rate_matcher)
+            .combine(&[Llr(4); 40], 0);
+
+        let rate_matcher = RateMatcher::new(48);
+        let buffer = buffers.get_or_insert(1, 0, rate_matcher);
+
+        assert_eq!(48, buffer.len());
+        assert_eq!(0, buffer.transmissions());
+        assert_eq!(0, count_received(buffer.codeword()));
+    }
+
+    #[test]
+    fn rate_matcher_change_clears_buffer() {
+        let k = 40;
+        let rate_matcher = RateMatcher::new(k);
+
+        for changed in [rate_matcher.with_ncb(96), rate_matcher.with_fillers(8)].iter() {
+            let mut buffers = HarqBuffers::new();
+            buffers
+                .get_or_insert(1, 0, rate_matcher)
+                .combine(&[Llr(4); 40], 0);
+            assert_eq!(1, buffers.get_or_insert(1, 0, rate_matcher).transmissions());
+
+            let buffer = buffers.get_or_insert(1, 0, *changed);
+            assert_eq!(changed, buffer.rate_matcher());
+            assert_eq!(0, buffer.transmissions());
+            assert_eq!(0, count_received(buffer.codeword()));
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! CCSDS Turbo Code Permutation
+use super::{Interleaver, InverseIter};
+
+/// CCSDS Turbo Code Permutation, see CCSDS 131.0-B 6.3.
+/// The information block is `k = k1 * k2` bits, with `k1 = 8` and `k2 = 223 * I` for the interleaving depths `I = 1, 2, 4, 5`.
+/// Permutation is computed using the formulas (with zero-based `s`):
+///    m = s mod 2,
+///    i = floor(s / (2 k2)),
+///    j = floor(s / 2) - i k2,
+///    t = (19 i + 1) mod (k1 / 2),
+///    q = t mod 8 + 1,
+///    c = (p_q j + 21 m) mod k2,
+///    pi(s) = 2 (t + c k1 / 2 + 1) - m - 1,
+/// where `p_1..p_8` are the primes 31, 37, 43, 47, 53, 59, 61 and 67.
+#[derive(Clone)]
+pub struct CcsdsPermutation {
+    /// The k2 parameter.
+    k2: usize,
+}
+
+/// The k1 parameter.
+const K1: usize = 8;
+
+/// The k2 parameter for the interleaving depth 1.
+const K2_BASE: usize = 223;
+
+/// The interleaving depths.
+const DEPTHS: [usize; 4] = [1, 2, 4, 5];
+
+/// The primes `p_1..p_8`.
+const PRIMES: [usize; 8] = [31, 37, 43, 47, 53, 59, 61, 67];
+
+/// Get the inverse of `a` modulo `n`, where `a` and `n` are coprime.
+pub(super) const fn modular_inverse(a: usize, n: usize) -> usize {
+    // Extended Euclidean algorithm, keeping only the coefficients of `a`.
+    let (mut r0, mut r1) = (n as isize, a as isize);
+    let (mut t0, mut t1) = (0isize, 1isize);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    t0.rem_euclid(n as isize) as usize
+}
+
+impl CcsdsPermutation {
+    /// Get the information block sizes `k` that have a permutation, 1784, 3568, 7136 and 8920.
+    pub fn block_sizes() -> impl Iterator<Item = usize> + Clone {
+        DEPTHS.iter().map(|&depth| K1 * K2_BASE * depth)
+    }
+
+    /// Get the permutation for block length `k`.
+    pub fn get(k: usize) -> Option<Self> {
+        if Self::block_sizes().any(|x| x == k) {
+            Some(Self { k2: k / K1 })
+        } else {
+            None
+        }
+    }
+
+    /// The block length.
+    pub const fn k(&self) -> usize {
+        K1 * self.k2
+    }
+
+    /// Get the interleaved index.
+    pub const fn pi(&self, s: usize) -> usize {
+        let m = s % 2;
+        let i = s / (2 * self.k2);
+        let j = s / 2 - i * self.k2;
+        let t = (19 * i + 1) % (K1 / 2);
+        let q = t % 8 + 1;
+        let c = (PRIMES[q - 1] * j + 21 * m) % self.k2;
+        2 * (t + c * K1 / 2 + 1) - m - 1
+    }
+
+    /// Get the de-interleaved index, by inverting the formulas:
+    ///    m = (pi + 1) mod 2,
+    ///    t = ((pi + 1 + m) / 2 - 1) mod (k1 / 2),
+    ///    c = floor(((pi + 1 + m) / 2 - 1) / (k1 / 2)),
+    ///    i = 3 (t - 1) mod 4,
+    ///    j = p_q^-1 (c - 21 m) mod k2,
+    ///    s = 2 (i k2 + j) + m.
+    pub const fn pi_inv(&self, pi: usize) -> usize {
+        let m = (pi + 1) % 2;
+        let x = (pi + 1 + m) / 2 - 1;
+        let t = x % (K1 / 2);
+        let c = x / (K1 / 2);
+        let i = 3 * (t + 3) % 4;
+        let q = t % 8 + 1;
+        let c = (c + self.k2 - 21 * m % self.k2) % self.k2;
+        let j = modular_inverse(PRIMES[q - 1], self.k2) * c % self.k2;
+        2 * (i * self.k2 + j) + m
+    }
+
+    /// Get an iterator that produces the permuted sequence.
+    pub fn iter(&self) -> CcsdsPermutationIterator {
+        CcsdsPermutationIterator {
+            permutation: self.clone(),
+            s: 0,
+        }
+    }
+}
+
+impl IntoIterator for CcsdsPermutation {
+    type Item = usize;
+
+    type IntoIter = CcsdsPermutationIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Interleaver for CcsdsPermutation {
+    type Iter<'a> = CcsdsPermutationIterator;
+
+    type IterInv<'a> = InverseIter<'a, Self>;
+
+    fn len(&self) -> usize {
+        self.k()
+    }
+
+    fn pi(&self, s: usize) -> usize {
+        CcsdsPermutation::pi(self, s)
+    }
+
+    fn pi_inv(&self, pi: usize) -> usize {
+        CcsdsPermutation::pi_inv(self, pi)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        CcsdsPermutation::iter(self)
+    }
+
+    fn iter_inv(&self) -> Self::IterInv<'_> {
+        InverseIter::new(self)
+    }
+}
+
+pub struct CcsdsPermutationIterator {
+    permutation: CcsdsPermutation,
+    s: usize,
+}
+
+impl Iterator for CcsdsPermutationIterator {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.s < self.permutation.k() {
+            let pi = self.permutation.pi(self.s);
+            self.s += 1;
+            Some(pi)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.permutation.k() - self.s;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for CcsdsPermutationIterator {}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn block_sizes() {
+        assert!(CcsdsPermutation::block_sizes().eq([1784, 3568, 7136, 8920].iter().copied()));
+        assert!(CcsdsPermutation::get(1785).is_none());
+        assert_eq!(8920, CcsdsPermutation::get(8920).unwrap().k());
+    }
+
+    #[test]
+    fn first_values() {
+        let permutation = CcsdsPermutation::get(1784).unwrap();
+        // s = 0: m = 0, t = 1, c = 0, pi = 2 * (1 + 0 + 1) - 0 - 1 = 3.
+        // s = 1: m = 1, t = 1, c = 21, pi = 2 * (1 + 84 + 1) - 1 - 1 = 170.
+        // s = 2: m = 0, t = 1, c = 37, pi = 2 * (1 + 148 + 1) - 0 - 1 = 299.
+        assert_eq!(3, permutation.pi(0));
+        assert_eq!(170, permutation.pi(1));
+        assert_eq!(299, permutation.pi(2));
+    }
+
+    #[test]
+    fn is_permutation() {
+        for k in CcsdsPermutation::block_sizes() {
+            let mut seen = vec![false; k];
+            for pi in CcsdsPermutation::get(k).unwrap() {
+                assert!(!seen[pi]);
+                seen[pi] = true;
+            }
+            assert!(seen.iter().all(|&x| x));
+        }
+    }
+
+    #[test]
+    fn pi_inv() {
+        for k in CcsdsPermutation::block_sizes() {
+            let permutation = CcsdsPermutation::get(k).unwrap();
+            for s in 0..k {
+                assert_eq!(s, permutation.pi_inv(permutation.pi(s)));
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use super::{Interleaver, InverseIter};
+
+/// Convolutional Turbo Code (CTC) Interleaver for the duo-binary Turbo codes of DVB-RCS and 802.16.
+/// The interleaving is done on `n` couples of bits `(A, B)` in two steps, see 802.16 8.4.9.2.3.2 and EN 301 790:
+/// 1. The bits of every other couple are switched, i.e. `(A, B) = (B, A)`, see `CtcStandard`.
+/// 2. The `j`'th couple of the interleaved sequence is the couple `i` of the sequence from step 1, with
+///    i = (p0 j + p + 1) mod n,
+///    where `p` is 0, `n/2 + p1`, `p2` and `n/2 + p3` for `j mod 4` being 0, 1, 2 and 3.
+#[derive(Clone)]
+pub struct CtcInterleaver {
+    /// The standard that defines which couples are switched.
+    standard: CtcStandard,
+    /// The number of couples.
+    n: usize,
+    /// The p0 to p3 parameters.
+    p: [usize; 4],
+}
+
+/// The standard of a CTC interleaver, which only differ in the couples that are switched in the first step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CtcStandard {
+    /// 802.16, which switches the couples with an odd index.
+    Wimax,
+    /// DVB-RCS (EN 301 790), which switches the couples with an even index.
+    DvbRcs,
+}
+
+impl CtcStandard {
+    /// Whether the bits of the couple `i` of the sequence in the natural order are switched.
+    pub const fn switched(self, i: usize) -> bool {
+        match self {
+            Self::Wimax => i % 2 == 1,
+            Self::DvbRcs => i.is_multiple_of(2),
+        }
+    }
+}
+
+impl CtcInterleaver {
+    pub const fn new(
+        standard: CtcStandard,
+        n: usize,
+        p0: usize,
+        p1: usize,
+        p2: usize,
+        p3: usize,
+    ) -> Self {
+        Self {
+            standard,
+            n,
+            p: [p0, p1, p2, p3],
+        }
+    }
+
+    /// The standard that defines which couples are switched.
+    pub const fn standard(&self) -> CtcStandard {
+        self.standard
+    }
+
+    /// The number of couples.
+    pub const fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Get the index of the couple that is the `j`'th couple of the interleaved sequence.
+    pub const fn pi(&self, j: usize) -> usize {
+        let p = match j % 4 {
+            0 => 0,
+            1 => self.n / 2 + self.p[1],
+            2 => self.p[2],
+            _ => self.n / 2 + self.p[3],
+        };
+        (self.p[0] * j + p + 1) % self.n
+    }
+
+    /// Whether the bits of the `j`'th couple of the interleaved sequence are switched.
+    pub const fn switched(&self, j: usize) -> bool {
+        self.standard.switched(self.pi(j))
+    }
+
+    /// Get an iterator that produces the interleaved couple indices.
+    pub fn iter(&self) -> CtcIterator {
+        CtcIterator {
+            interleaver: self.clone(),
+            j: 0,
+        }
+    }
+}
+
+/// The interleaving of the couples, without the switching of their bits.
+/// The inverse is found by searching the interleaved sequence.
+impl Interleaver for CtcInterleaver {
+    type Iter<'a> = CtcIterator;
+
+    type IterInv<'a> = InverseIter<'a, Self>;
+
+    fn len(&self) -> usize {
+        self.n
+    }
+
+    fn pi(&self, i: usize) -> usize {
+        CtcInterleaver::pi(self, i)
+    }
+
+    fn pi_inv(&self, j: usize) -> usize {
+        CtcInterleaver::iter(self)
+            .position(|pi| pi == j)
+            .expect("The CTC interleaver is not a permutation.")
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        CtcInterleaver::iter(self)
+    }
+
+    fn iter_inv(&self) -> Self::IterInv<'_> {
+        InverseIter::new(self)
+    }
+
+    fn has_fast_inverse(&self) -> bool {
+        false
+    }
+}
+
+impl IntoIterator for CtcInterleaver {
+    type Item = usize;
+
+    type IntoIter = CtcIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[derive(Clone)]
+pub struct CtcIterator {
+    interleaver: CtcInterleaver,
+    j: usize,
+}
+
+impl Iterator for CtcIterator {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.j < self.interleaver.n {
+            let pi = self.interleaver.pi(self.j);
+            self.j += 1;
+            Some(pi)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.interleaver.n - self.j;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for CtcIterator {}
+
+/// The number of couples and the p0 to p3 parameters of each interleaver of a standard.
+type CtcParameters = [(u16, u8, u16, u16, u16)];
+
+/// Get the interleaver for `n` couples from the `parameters` of the `standard`.
+fn lookup(standard: CtcStandard, parameters: &CtcParameters, n: usize) -> Option<CtcInterleaver> {
+    parameters
+        .iter()
+        .find(|x| x.0 as usize == n)
+        .map(|&(n, p0, p1, p2, p3)| {
+            CtcInterleaver::new(
+                standard,
+                n as usize,
+                p0 as usize,
+                p1 as usize,
+                p2 as usize,
+                p3 as usize,
+            )
+        })
+}
+
+/// 802.16 CTC Interleaver.
+pub struct WimaxCtc;
+
+impl WimaxCtc {
+    /// The number of couples and the p0 to p3 parameters, see 802.16 Table 8-513.
+    const PARAMETERS: [(u16, u8, u16, u16, u16); 17] = [
+        (24, 5, 0, 0, 0),
+        (36, 11, 18, 0, 18),
+        (48, 13, 24, 0, 24),
+        (72, 11, 6, 0, 6),
+        (96, 7, 48, 24, 72),
+        (108, 11, 54, 56, 2),
+        (120, 13, 60, 0, 60),
+        (144, 17, 74, 72, 2),
+        (180, 11, 90, 0, 90),
+        (192, 11, 96, 48, 144),
+        (216, 13, 108, 0, 108),
+        (240, 13, 120, 60, 180),
+        (480, 53, 62, 12, 2),
+        (960, 43, 64, 300, 824),
+        (1440, 43, 720, 360, 540),
+        (1920, 31, 8, 24, 16),
+        (2400, 53, 66, 24, 2),
+    ];
+
+    /// Get the numbers of couples `n` that have an interleaver.
+    pub fn block_sizes() -> impl Iterator<Item = usize> + Clone {
+        Self::PARAMETERS.iter().map(|x| x.0 as usize)
+    }
+
+    /// Get the interleaver for `n` couples.
+    pub fn get(n: usize) -> Option<CtcInterleaver> {
+        lookup(CtcStandard::Wimax, &Self::PARAMETERS, n)
+    }
+}
+
+/// DVB-RCS CTC Interleaver.
+pub struct DvbRcsCtc;
+
+impl DvbRcsCtc {
+    /// The number of couples and the p0 to p3 parameters for the frame sizes of 12 to 216 bytes, see EN 301 790.
+    const PARAMETERS: [(u16, u8, u16, u16, u16); 12] = [
+        (48, 11, 24, 0, 24),
+        (64, 7, 34, 32, 2),
+        (212, 13, 106, 108, 2),
+        (220, 23, 112, 4, 116),
+        (228, 17, 116, 72, 188),
+        (424, 11, 6, 8, 2),
+        (432, 13, 0, 4, 8),
+        (440, 13, 10, 4, 2),
+        (752, 19, 376, 224, 600),
+        (848, 19, 2, 16, 6),
+        (856, 19, 428, 224, 652),
+        (864, 19, 2, 16, 6),
+    ];
+
+    /// Get the numbers of couples `n` that have an interleaver.
+    pub fn block_sizes() -> impl Iterator<Item = usize> + Clone {
+        Self::PARAMETERS.iter().map(|x| x.0 as usize)
+    }
+
+    /// Get the interleaver for `n` couples.
+    pub fn get(n: usize) -> Option<CtcInterleaver> {
+        lookup(CtcStandard::DvbRcs, &Self::PARAMETERS, n)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn first_values() {
+        let interleaver = WimaxCtc::get(24).unwrap();
+        assert_eq!(
+            vec![1, 18, 11, 4, 21, 14, 7, 0],
+            interleaver.iter().take(8).collect::<Vec<_>>()
+        );
+        assert!(interleaver.switched(0));
+        assert!(!interleaver.switched(1));
+    }
+
+    #[test]
+    fn first_values_dvb_rcs() {
+        let interleaver = DvbRcsCtc::get(48).unwrap();
+        assert_eq!(CtcStandard::DvbRcs, interleaver.standard());
+        assert_eq!(
+            vec![1, 12, 23, 34, 45, 8, 19, 30],
+            interleaver.iter().take(8).collect::<Vec<_>>()
+        );
+        assert!(!interleaver.switched(0));
+        assert!(interleaver.switched(1));
+
+        // The same permutation switches the other couples in 802.16.
+        let wimax = CtcInterleaver::new(CtcStandard::Wimax, 48, 11, 24, 0, 24);
+        assert!((0..48).all(|j| wimax.switched(j) != interleaver.switched(j)));
+
+        assert_eq!(
+            vec![1, 356, 119, 402, 213, 88, 331, 134],
+            WimaxCtc::get(480)
+                .unwrap()
+                .iter()
+                .take(8)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn is_permutation() {
+        let interleavers = WimaxCtc::block_sizes()
+            .map(|n| WimaxCtc::get(n).unwrap())
+            .chain(DvbRcsCtc::block_sizes().map(|n| DvbRcsCtc::get(n).unwrap()));
+        for interleaver in interleavers {
+            let n = interleaver.n();
+            assert!(n.is_multiple_of(4));
+            let mut seen = vec![false; n];
+            for pi in interleaver.clone() {
+                assert!(!seen[pi]);
+                seen[pi] = true;
+            }
+            assert!(seen.iter().all(|&x| x));
+            assert!(Interleaver::iter_inv(&interleaver)
+                .enumerate()
+                .step_by(7)
+                .all(|(j, i)| interleaver.pi(i) == j));
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::qpp::Qpp;
+use super::qpp::{Qpp, QppTable};
 
 /// LTE QPP Interleaver.
 pub struct LteQpp;
@@ -204,13 +204,32 @@ impl LteQpp {
         (263, 480),
     ];
 
+    /// Get the supported block lengths `K` in increasing order, see 36.212 table 5.1.3-3.
+    pub fn block_sizes() -> impl Iterator<Item = usize> + Clone {
+        (5 * 8..=64 * 8)
+            .step_by(8)
+            .chain((66 * 8..=128 * 8).step_by(16))
+            .chain((132 * 8..=256 * 8).step_by(32))
+            .chain((264 * 8..=768 * 8).step_by(64))
+    }
+
     /// Get an interleaver for the block length `k` (in bits).
     pub fn get(k: usize) -> Option<Qpp> {
-        Self::get_params(k).map(|(f1, f2)| Qpp::new(k, f1, f2))
+        Self::get_params(k).map(|(f1, f2)| Qpp::new(k, f1, f2).with_inverse())
+    }
+
+    /// Get the permutation tables for the block length `K` (in bits), e.g. for placing them in flash with
+    /// `static INTERLEAVER: QppTable<6144> = LteQpp::table();`.
+    /// Panics if `K` is not a supported block length.
+    pub const fn table<const K: usize>() -> QppTable<K> {
+        match Self::get_params(K) {
+            Some((f1, f2)) => QppTable::new(f1, f2),
+            None => panic!("The block length is not supported."),
+        }
     }
 
-    fn get_params(k: usize) -> Option<(usize, usize)> {
-        if (5 * 8..=64 * 8).contains(&k) {
+    const fn get_params(k: usize) -> Option<(usize, usize)> {
+        if 5 * 8 <= k && k <= 64 * 8 {
             let index = (k - 5 * 8) / 8;
             if 5 * 8 + index * 8 == k {
                 let (f1, f2) = Self::F_K5_K64_STEP1[index];
@@ -218,7 +237,7 @@ impl LteQpp {
             } else {
                 None
             }
-        } else if (66 * 8..=128 * 8).contains(&k) {
+        } else if 66 * 8 <= k && k <= 128 * 8 {
             let index = (k - 66 * 8) / 16;
             if 66 * 8 + index * 16 == k {
                 let (f1, f2) = Self::F_K66_K128_STEP2[index];
@@ -226,7 +245,7 @@ impl LteQpp {
             } else {
                 None
             }
-        } else if (132 * 8..=256 * 8).contains(&k) {
+        } else if 132 * 8 <= k && k <= 256 * 8 {
             let index = (k - 132 * 8) / 32;
             if 132 * 8 + index * 32 == k {
                 let (f1, f2) = Self::F_K132_K256_STEP4[index];
@@ -234,7 +253,7 @@ impl LteQpp {
             } else {
                 None
             }
-        } else if (264 * 8..=768 * 8).contains(&k) {
+        } else if 264 * 8 <= k && k <= 768 * 8 {
             let index = (k - 264 * 8) / 64;
             if 264 * 8 + index * 64 == k {
                 let (f1, f2) = Self::F_K264_K768_STEP8[index];
@@ -251,6 +270,7 @@ impl LteQpp {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::interleavers::Interleaver;
 
     #[test]
     fn get_params() {
@@ -272,4 +292,21 @@ pub mod tests {
         assert_eq!(Some((263, 480)), LteQpp::get_params(768 * 8));
         assert_eq!(None, LteQpp::get_params(769 * 8));
     }
+
+    #[test]
+    fn table() {
+        static TABLE: QppTable<6144> = LteQpp::table();
+        const SMALL_TABLE: QppTable<40> = LteQpp::table();
+
+        let qpp = LteQpp::get(6144).unwrap();
+        assert!(TABLE.iter().eq(qpp.iter()));
+        assert!(TABLE.iter_inv().eq(qpp.iter_inv()));
+        assert!(SMALL_TABLE.iter().eq(LteQpp::get(40).unwrap().iter()));
+    }
+
+    #[test]
+    fn block_sizes() {
+        assert_eq!(188, LteQpp::block_sizes().count());
+        assert!(LteQpp::block_sizes().all(|k| LteQpp::get_params(k).is_some()));
+    }
 }
@@ -1,2 +1,274 @@
+pub mod ccsds;
+pub mod ctc;
 pub mod lte;
 pub mod qpp;
+pub mod qpp_search;
+pub mod table;
+pub mod umts;
+
+/// A permutation of a block of `len()` elements.
+/// The `i`'th element of the interleaved sequence is the element `pi(i)` of the original sequence,
+/// and the element `j` of the original sequence is the element `pi_inv(j)` of the interleaved sequence.
+pub trait Interleaver {
+    /// The iterator that produces the permuted sequence.
+    type Iter<'a>: Iterator<Item = usize>
+    where
+        Self: 'a;
+
+    /// The iterator that produces the inverse permuted sequence.
+    type IterInv<'a>: Iterator<Item = usize>
+    where
+        Self: 'a;
+
+    /// The block length.
+    fn len(&self) -> usize;
+
+    /// Whether the block is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the interleaved index.
+    fn pi(&self, i: usize) -> usize;
+
+    /// Get the de-interleaved index, i.e. the index `i` for which `pi(i) = j`.
+    fn pi_inv(&self, j: usize) -> usize;
+
+    /// Get an iterator that produces the permuted sequence `pi(0), pi(1), ...`.
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Get an iterator that produces the inverse permuted sequence `pi_inv(0), pi_inv(1), ...`.
+    /// An interleaver without a faster way can use `InverseIter`.
+    fn iter_inv(&self) -> Self::IterInv<'_>;
+
+    /// Whether `pi_inv()` and `iter_inv()` are about as fast as `pi()` and `iter()`.
+    /// De-interleaving should scatter with `iter()` instead of gathering with `iter_inv()` if they are not.
+    fn has_fast_inverse(&self) -> bool {
+        true
+    }
+
+    /// Get the inverse interleaver, i.e. the de-interleaver.
+    fn inverse(self) -> Inverse<Self>
+    where
+        Self: Sized,
+    {
+        Inverse(self)
+    }
+
+    /// Get the interleaver that interleaves with `self` and then with `next`.
+    fn then<J: Interleaver>(self, next: J) -> Composition<Self, J>
+    where
+        Self: Sized,
+    {
+        assert_eq!(self.len(), next.len());
+        Composition { first: self, next }
+    }
+}
+
+impl<T: Interleaver + ?Sized> Interleaver for &T {
+    type Iter<'a>
+        = T::Iter<'a>
+    where
+        Self: 'a;
+
+    type IterInv<'a>
+        = T::IterInv<'a>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn pi(&self, i: usize) -> usize {
+        (**self).pi(i)
+    }
+
+    fn pi_inv(&self, j: usize) -> usize {
+        (**self).pi_inv(j)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        (**self).iter()
+    }
+
+    fn iter_inv(&self) -> Self::IterInv<'_> {
+        (**self).iter_inv()
+    }
+
+    fn has_fast_inverse(&self) -> bool {
+        (**self).has_fast_inverse()
+    }
+}
+
+/// Iterator that produces the inverse permuted sequence by invoking `pi_inv` for each index.
+pub struct InverseIter<'a, I: Interleaver + ?Sized> {
+    interleaver: &'a I,
+    j: usize,
+}
+
+impl<'a, I: Interleaver + ?Sized> InverseIter<'a, I> {
+    pub const fn new(interleaver: &'a I) -> Self {
+        Self { interleaver, j: 0 }
+    }
+}
+
+impl<'a, I: Interleaver + ?Sized> Iterator for InverseIter<'a, I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.j < self.interleaver.len() {
+            let i = self.interleaver.pi_inv(self.j);
+            self.j += 1;
+            Some(i)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.interleaver.len() - self.j;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, I: Interleaver + ?Sized> ExactSizeIterator for InverseIter<'a, I> {}
+
+/// The inverse of an interleaver, see `Interleaver::inverse()`.
+#[derive(Clone)]
+pub struct Inverse<I: Interleaver>(I);
+
+impl<I: Interleaver> Interleaver for Inverse<I> {
+    type Iter<'a>
+        = I::IterInv<'a>
+    where
+        Self: 'a;
+
+    type IterInv<'a>
+        = I::Iter<'a>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn pi(&self, i: usize) -> usize {
+        self.0.pi_inv(i)
+    }
+
+    fn pi_inv(&self, j: usize) -> usize {
+        self.0.pi(j)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter_inv()
+    }
+
+    fn iter_inv(&self) -> Self::IterInv<'_> {
+        self.0.iter()
+    }
+}
+
+/// The composition of two interleavers, see `Interleaver::then()`.
+/// The interleaved index is `first.pi(next.pi(i))`.
+#[derive(Clone)]
+pub struct Composition<I: Interleaver, J: Interleaver> {
+    first: I,
+    next: J,
+}
+
+impl<I: Interleaver, J: Interleaver> Interleaver for Composition<I, J> {
+    type Iter<'a>
+        = CompositionIter<J::Iter<'a>, &'a I>
+    where
+        Self: 'a;
+
+    type IterInv<'a>
+        = CompositionIter<I::IterInv<'a>, Inverse<&'a J>>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.next.len()
+    }
+
+    fn pi(&self, i: usize) -> usize {
+        self.first.pi(self.next.pi(i))
+    }
+
+    fn pi_inv(&self, j: usize) -> usize {
+        self.next.pi_inv(self.first.pi_inv(j))
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        CompositionIter {
+            inner: self.next.iter(),
+            outer: &self.first,
+        }
+    }
+
+    fn iter_inv(&self) -> Self::IterInv<'_> {
+        CompositionIter {
+            inner: self.first.iter_inv(),
+            outer: Inverse(&self.next),
+        }
+    }
+
+    fn has_fast_inverse(&self) -> bool {
+        self.first.has_fast_inverse() && self.next.has_fast_inverse()
+    }
+}
+
+/// Iterator that maps the indices produced by the `inner` iterator through the `outer` interleaver.
+pub struct CompositionIter<It: Iterator<Item = usize>, O: Interleaver> {
+    inner: It,
+    outer: O,
+}
+
+impl<It: Iterator<Item = usize>, O: Interleaver> Iterator for CompositionIter<It, O> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.inner.next().map(|i| self.outer.pi(i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{qpp::Qpp, table::TableInterleaver, *};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn inverse() {
+        let qpp = Qpp::new(40, 3, 10);
+        let inverse = qpp.clone().inverse();
+        assert_eq!(40, inverse.len());
+        for i in 0..40 {
+            assert_eq!(i, inverse.pi(qpp.pi(i)));
+            assert_eq!(i, inverse.pi_inv(inverse.pi(i)));
+        }
+        assert_eq!(
+            qpp.iter_inv().collect::<Vec<_>>(),
+            inverse.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn composition() {
+        let first = TableInterleaver::new(&[2, 0, 3, 1]).unwrap();
+        let next = TableInterleaver::new(&[1, 3, 0, 2]).unwrap();
+        let composition = (&first).then(&next);
+        assert_eq!(vec![0, 1, 2, 3], composition.iter().collect::<Vec<_>>());
+
+        // An interleaver followed by its inverse is the identity.
+        let qpp = Qpp::new(40, 3, 10);
+        let identity = (&qpp).then(qpp.clone().inverse());
+        assert!(identity.iter().eq(0..40));
+        assert!((0..40).all(|j| identity.pi_inv(j) == j));
+    }
+}
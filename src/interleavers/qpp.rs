@@ -1,3 +1,8 @@
+use super::{Interleaver, InverseIter};
+use alloc::vec::Vec;
+use core::{iter::Map, slice};
+use streaming_iterator::StreamingIterator;
+
 /// Quadratic Polynomial Permutation (QPP) Interleaver.
 /// Permutation is computed using the formula:
 ///    pi(i) = (f1 * i + f2 * i^2) mod k.
@@ -6,6 +11,9 @@
 /// where
 ///    g(i+1) = (g(i) + (2f2 mod k)) mod k,
 /// with `2f2 mod k` being constant for each iteration.
+///
+/// The inverse permutation is also a permutation polynomial, see `QppInverse`.
+/// It is computed in `O(k)` time by `try_new()` and `with_inverse()`.
 #[derive(Clone)]
 pub struct Qpp {
     /// The block length.
@@ -14,18 +22,136 @@ pub struct Qpp {
     f1: usize,
     /// The f2 parameter
     f2: usize,
+    /// The inverse permutation polynomial, if it is computed and exists.
+    inverse: Option<QppInverse>,
+}
+
+/// The reason why QPP parameters are not a permutation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QppError {
+    /// The block length is zero.
+    ZeroLength,
+    /// `f1` and `k` have the common `factor`.
+    NotCoprime { factor: usize },
+    /// The `prime` factor of `k` does not divide `f2`.
+    MissingFactor { prime: usize },
+}
+
+/// The largest degree of an inverse permutation polynomial that is searched for.
+/// The inverses of the LTE interleavers have degree 4 or less.
+const MAX_INVERSE_DEGREE: usize = 12;
+
+/// Get the greatest common divisor of `a` and `b`.
+pub(super) const fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Evaluate `(f1 * i + f2 * i^2) mod k` without overflow.
+const fn polynomial(k: usize, f1: usize, f2: usize, i: usize) -> usize {
+    let (k, i) = (k as u64, (i % k) as u64);
+    ((f1 as u64 % k * i + f2 as u64 % k * (i * i % k)) % k) as usize
+}
+
+/// Evaluate the binomial coefficient `C(n, m) mod k`.
+/// The falling factorial `n (n - 1) ... (n - m + 1)` is divisible by `m!`,
+/// so it is computed modulo `k m!` before the division.
+const fn binomial(n: usize, m: usize, k: usize) -> usize {
+    if n < m {
+        return 0;
+    }
+
+    let mut factorial: u128 = 1;
+    let mut l = 2;
+    while l <= m {
+        factorial *= l as u128;
+        l += 1;
+    }
+
+    let modulus = k as u128 * factorial;
+    let mut product: u128 = 1;
+    let mut l = 0;
+    while l < m {
+        product = product * ((n - l) as u128 % modulus) % modulus;
+        l += 1;
+    }
+    (product / factorial) as usize
 }
 
 impl Qpp {
+    /// Create a QPP interleaver without computing the inverse permutation polynomial,
+    /// so that de-interleaving searches the permuted sequence, see `with_inverse()`.
     pub const fn new(k: usize, f1: usize, f2: usize) -> Self {
-        Self { k, f1, f2 }
+        Self {
+            k,
+            f1,
+            f2,
+            inverse: None,
+        }
+    }
+
+    /// Compute the inverse permutation polynomial used for de-interleaving, see `inverse_polynomial()`.
+    pub const fn with_inverse(mut self) -> Self {
+        self.inverse = QppInverse::compute(self.k, self.f1, self.f2);
+        self
+    }
+
+    /// Create a QPP interleaver after validating that the parameters are a permutation,
+    /// i.e. that `f1` is coprime to `k`, and that every prime factor of `k` divides `f2`.
+    /// The conditions are sufficient for all `k`, and also necessary unless `k` is 2 modulo 4.
+    /// The inverse permutation polynomial is also computed.
+    pub const fn try_new(k: usize, f1: usize, f2: usize) -> Result<Self, QppError> {
+        match Self::validate(k, f1, f2) {
+            Ok(()) => Ok(Self::new(k, f1, f2).with_inverse()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Validate the parameters, see `try_new()`.
+    pub const fn validate(k: usize, f1: usize, f2: usize) -> Result<(), QppError> {
+        if k == 0 {
+            return Err(QppError::ZeroLength);
+        }
+
+        let factor = gcd(f1, k);
+        if factor != 1 {
+            return Err(QppError::NotCoprime { factor });
+        }
+
+        let mut rest = k;
+        let mut prime = 2;
+        while rest > 1 {
+            if prime * prime > rest {
+                prime = rest;
+            }
+            if rest.is_multiple_of(prime) {
+                if !f2.is_multiple_of(prime) {
+                    return Err(QppError::MissingFactor { prime });
+                }
+                while rest.is_multiple_of(prime) {
+                    rest /= prime;
+                }
+            }
+            prime += 1;
+        }
+
+        Ok(())
     }
 
     /// Get the interleaved index.
     /// It is slower to call this function `k` times than iterating the entire
     /// permuted sequence.
     pub const fn pi(&self, i: usize) -> usize {
-        (self.f1 * i + self.f2 * i * i) % self.k
+        polynomial(self.k, self.f1, self.f2, i)
+    }
+
+    /// Get the inverse permutation polynomial, which is `None` if it is not computed,
+    /// if the interleaver is not a permutation, or if the degree of the inverse is larger than 12.
+    pub const fn inverse_polynomial(&self) -> Option<&QppInverse> {
+        self.inverse.as_ref()
     }
 
     /// Get an iterator that produces the permuted sequence.
@@ -33,12 +159,46 @@ impl Qpp {
     pub fn iter(&self) -> QppIterator {
         QppIterator {
             k: self.k,
+            f1: self.f1,
+            f2: self.f2,
             two_f2_mod_k: (2 * self.f2) % self.k,
             pi: 0,
             g: (self.f1 + self.f2) % self.k,
             i: 0,
+            end: self.k,
         }
     }
+
+    /// Get an iterator that produces the interleaved indices of `parallelism` sub-blocks of `m = k / parallelism`
+    /// indices at once, i.e. `pi(i), pi(i + m), ..., pi(i + (parallelism - 1) m)` for the step `i`.
+    /// If the interleaved data is stored in `parallelism` memory banks of `m` elements,
+    /// with the index `pi` stored in the bank `pi / m` at `pi mod m`,
+    /// the indices of a step are in different banks by the contention-free property of QPP interleavers.
+    /// The indices of a step are also at the same address `pi(i) mod m` within their banks.
+    /// Returns `None` if `parallelism` does not divide `k`.
+    pub fn contention_free(&self, parallelism: usize) -> Option<QppParallelIterator> {
+        if parallelism == 0 || !self.k.is_multiple_of(parallelism) {
+            return None;
+        }
+
+        let m = self.k / parallelism;
+        let sub_blocks = (0..parallelism)
+            .map(|t| {
+                let mut iterator = self.iter();
+                iterator.end = (t + 1) * m;
+                if t > 0 {
+                    iterator.nth(t * m - 1);
+                }
+                iterator
+            })
+            .collect();
+
+        Some(QppParallelIterator {
+            m,
+            sub_blocks,
+            indices: Vec::with_capacity(parallelism),
+        })
+    }
 }
 
 impl IntoIterator for Qpp {
@@ -51,19 +211,94 @@ impl IntoIterator for Qpp {
     }
 }
 
+/// The de-interleaving of a QPP interleaver is computed from its inverse polynomial,
+/// and searches the permuted sequence if there is none, see `Qpp::inverse_polynomial()`.
+impl Interleaver for Qpp {
+    type Iter<'a> = QppIterator;
+
+    type IterInv<'a> = QppInverseIter<'a>;
+
+    fn len(&self) -> usize {
+        self.k
+    }
+
+    fn pi(&self, i: usize) -> usize {
+        Qpp::pi(self, i)
+    }
+
+    fn pi_inv(&self, j: usize) -> usize {
+        match &self.inverse {
+            Some(inverse) => inverse.pi(j),
+            None => Qpp::iter(self)
+                .position(|pi| pi == j)
+                .expect("The QPP is not a permutation."),
+        }
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        Qpp::iter(self)
+    }
+
+    fn iter_inv(&self) -> Self::IterInv<'_> {
+        match &self.inverse {
+            Some(inverse) => QppInverseIter::Polynomial(inverse.iter()),
+            None => QppInverseIter::Search(InverseIter::new(self)),
+        }
+    }
+
+    fn has_fast_inverse(&self) -> bool {
+        self.inverse.is_some()
+    }
+}
+
+/// The inverse permuted sequence of a QPP interleaver, see `Qpp::iter_inv()`.
+pub enum QppInverseIter<'a> {
+    /// Iterate the inverse permutation polynomial.
+    Polynomial(QppInverseIterator),
+    /// Search the permuted sequence for each index.
+    Search(InverseIter<'a, Qpp>),
+}
+
+impl Iterator for QppInverseIter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        match self {
+            Self::Polynomial(iterator) => iterator.next(),
+            Self::Search(iterator) => iterator.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Polynomial(iterator) => iterator.size_hint(),
+            Self::Search(iterator) => iterator.size_hint(),
+        }
+    }
+}
+
+impl ExactSizeIterator for QppInverseIter<'_> {}
+
 pub struct QppIterator {
     k: usize,
+    f1: usize,
+    f2: usize,
     two_f2_mod_k: usize,
+    /// The next interleaved index from the front.
     pi: usize,
+    /// The increment of the next interleaved index from the front.
     g: usize,
+    /// The next index from the front.
     i: usize,
+    /// The index after the next index from the back.
+    end: usize,
 }
 
 impl Iterator for QppIterator {
     type Item = usize;
 
     fn next(&mut self) -> Option<usize> {
-        if self.i < self.k {
+        if self.i < self.end {
             let pi = self.pi;
             let g = self.g;
 
@@ -78,15 +313,336 @@ impl Iterator for QppIterator {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.k, Some(self.k))
+        let remaining = self.end - self.i;
+        (remaining, Some(remaining))
+    }
+
+    /// Skip `n` permutations in constant time.
+    fn nth(&mut self, n: usize) -> Option<usize> {
+        if n >= self.end - self.i {
+            self.i = self.end;
+            return None;
+        }
+
+        self.i += n;
+        self.pi = polynomial(self.k, self.f1, self.f2, self.i);
+        self.g = (polynomial(self.k, self.f1, self.f2, self.i + 1) + self.k - self.pi) % self.k;
+        self.next()
+    }
+}
+
+impl DoubleEndedIterator for QppIterator {
+    fn next_back(&mut self) -> Option<usize> {
+        if self.i < self.end {
+            self.end -= 1;
+            Some(polynomial(self.k, self.f1, self.f2, self.end))
+        } else {
+            None
+        }
     }
 }
 
 impl ExactSizeIterator for QppIterator {}
 
+/// An interleaved index of a sub-block, see `Qpp::contention_free()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParallelIndex {
+    /// The interleaved index.
+    pub pi: usize,
+    /// The memory bank of the index.
+    pub bank: usize,
+    /// The address of the index within the bank.
+    pub address: usize,
+}
+
+pub struct QppParallelIterator {
+    /// The sub-block length.
+    m: usize,
+    /// The iterators of each sub-block.
+    sub_blocks: Vec<QppIterator>,
+    /// The indices of the current step.
+    indices: Vec<ParallelIndex>,
+}
+
+impl StreamingIterator for QppParallelIterator {
+    type Item = [ParallelIndex];
+
+    fn advance(&mut self) {
+        let m = self.m;
+        self.indices.clear();
+        for iterator in self.sub_blocks.iter_mut() {
+            if let Some(pi) = iterator.next() {
+                self.indices.push(ParallelIndex {
+                    pi,
+                    bank: pi / m,
+                    address: pi % m,
+                });
+            }
+        }
+    }
+
+    fn get(&self) -> Option<&Self::Item> {
+        if self.indices.is_empty() {
+            None
+        } else {
+            Some(&self.indices)
+        }
+    }
+}
+
+/// The permutation tables of a QPP interleaver for the block length `K`, computed by a `const fn`,
+/// so that they can be placed in flash instead of iterating the QPP in each Turbo decoder iteration.
+/// The indices are stored as `u16`, which limits `K` to 65535.
+#[derive(Clone)]
+pub struct QppTable<const K: usize> {
+    /// The interleaved indices.
+    pi: [u16; K],
+    /// The de-interleaved indices.
+    pi_inv: [u16; K],
+}
+
+pub type QppTableIterator<'a> = Map<slice::Iter<'a, u16>, fn(&u16) -> usize>;
+
+impl<const K: usize> QppTable<K> {
+    /// Compute the tables of the QPP with the parameters `f1` and `f2`.
+    /// Panics if `K` is too large or the QPP is not a permutation.
+    pub const fn new(f1: usize, f2: usize) -> Self {
+        assert!(K <= u16::MAX as usize, "The block length is too large.");
+
+        // Iterate the QPP using the recursion of `QppIterator`.
+        let mut pi = [0; K];
+        let mut pi_inv = [u16::MAX; K];
+        let (mut index, mut g) = (0, (f1 + f2) % K);
+        let two_f2_mod_k = (2 * f2) % K;
+        let mut i = 0;
+        while i < K {
+            assert!(
+                pi_inv[index] == u16::MAX,
+                "The parameters are not a permutation."
+            );
+            pi[i] = index as u16;
+            pi_inv[index] = i as u16;
+            index = (index + g) % K;
+            g = (g + two_f2_mod_k) % K;
+            i += 1;
+        }
+
+        Self { pi, pi_inv }
+    }
+
+    /// Get the interleaved indices.
+    pub const fn as_slice(&self) -> &[u16] {
+        &self.pi
+    }
+}
+
+impl<const K: usize> Interleaver for QppTable<K> {
+    type Iter<'a> = QppTableIterator<'a>;
+
+    type IterInv<'a> = QppTableIterator<'a>;
+
+    fn len(&self) -> usize {
+        K
+    }
+
+    fn pi(&self, i: usize) -> usize {
+        self.pi[i] as usize
+    }
+
+    fn pi_inv(&self, j: usize) -> usize {
+        self.pi_inv[j] as usize
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.pi.iter().map(|&x| x as usize)
+    }
+
+    fn iter_inv(&self) -> Self::IterInv<'_> {
+        self.pi_inv.iter().map(|&x| x as usize)
+    }
+}
+
+impl<'a, const K: usize> IntoIterator for &'a QppTable<K> {
+    type Item = usize;
+
+    type IntoIter = QppTableIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pi.iter().map(|&x| x as usize)
+    }
+}
+
+/// The inverse of a QPP interleaver, which is a permutation polynomial of degree `d`:
+///    pi_inv(j) = (c_0 + c_1 C(j, 1) + c_2 C(j, 2) + ... + c_d C(j, d)) mod k,
+/// where `C(j, m)` is the binomial coefficient.
+/// The coefficients are in the Newton (binomial) basis, `c_m` being the `m`'th forward difference of `pi_inv` at 0,
+/// as they always exist in `Z_k`, while the coefficients of the powers of `j` may need divisions by `m!`.
+/// The inverse sequence is iterated with `d` additions per index, like `QppIterator` iterates the QPP with two.
+#[derive(Clone, Debug)]
+pub struct QppInverse {
+    /// The block length.
+    k: usize,
+    /// The coefficients `c_0` to `c_d`.
+    coefficients: [usize; MAX_INVERSE_DEGREE + 1],
+    /// The degree `d`.
+    degree: usize,
+}
+
+impl QppInverse {
+    /// Compute the inverse of the QPP with the parameters `k`, `f1` and `f2`,
+    /// and verify that it is the inverse for all indices.
+    const fn compute(k: usize, f1: usize, f2: usize) -> Option<Self> {
+        if k == 0 {
+            return None;
+        }
+
+        // Find pi_inv(j) for the points j = 0..n needed to interpolate a polynomial of the largest degree.
+        let n = if k < MAX_INVERSE_DEGREE + 1 {
+            k
+        } else {
+            MAX_INVERSE_DEGREE + 1
+        };
+        let mut values = [usize::MAX; MAX_INVERSE_DEGREE + 1];
+        let mut i = 0;
+        while i < k {
+            let pi = polynomial(k, f1, f2, i);
+            if pi < n {
+                values[pi] = i;
+            }
+            i += 1;
+        }
+
+        // Compute the forward differences in place, such that values[m] becomes the m'th difference at 0.
+        let mut m = 0;
+        while m < n {
+            if values[m] == usize::MAX {
+                return None;
+            }
+            m += 1;
+        }
+        let mut m = 1;
+        while m < n {
+            let mut j = n - 1;
+            while j >= m {
+                values[j] = (values[j] + k - values[j - 1]) % k;
+                j -= 1;
+            }
+            m += 1;
+        }
+
+        let mut degree = n - 1;
+        while degree > 0 && values[degree] == 0 {
+            degree -= 1;
+        }
+        let inverse = Self {
+            k,
+            coefficients: values,
+            degree,
+        };
+
+        // Verify that pi(pi_inv(j)) = j for all j.
+        let mut iterator = inverse.iter();
+        let mut j = 0;
+        while j < k {
+            let i = iterator.step();
+            if polynomial(k, f1, f2, i) != j {
+                return None;
+            }
+            j += 1;
+        }
+
+        Some(inverse)
+    }
+
+    /// The degree of the polynomial.
+    pub const fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// The coefficients `c_0` to `c_d` in the Newton basis.
+    pub fn coefficients(&self) -> &[usize] {
+        &self.coefficients[..=self.degree]
+    }
+
+    /// Get the de-interleaved index.
+    /// It is slower to call this function `k` times than iterating the entire
+    /// inverse permuted sequence.
+    pub const fn pi(&self, j: usize) -> usize {
+        self.difference(0, j)
+    }
+
+    /// Evaluate the `m`'th forward difference of the polynomial at `j`, i.e.
+    ///    c_m + c_(m+1) C(j, 1) + ... + c_d C(j, d - m).
+    const fn difference(&self, m: usize, j: usize) -> usize {
+        let mut sum = 0;
+        let mut l = m;
+        while l <= self.degree {
+            let term = self.coefficients[l] as u128 * binomial(j, l - m, self.k) as u128;
+            sum = (sum + (term % self.k as u128) as usize) % self.k;
+            l += 1;
+        }
+        sum
+    }
+
+    /// Get an iterator that produces the inverse permuted sequence.
+    /// It produces `k` de-interleaved indices and is faster than invoking `pi` `k` times.
+    pub const fn iter(&self) -> QppInverseIterator {
+        QppInverseIterator {
+            k: self.k,
+            differences: self.coefficients,
+            degree: self.degree,
+            j: 0,
+        }
+    }
+}
+
+pub struct QppInverseIterator {
+    k: usize,
+    /// The forward differences of the polynomial at the next index.
+    differences: [usize; MAX_INVERSE_DEGREE + 1],
+    degree: usize,
+    /// The next index.
+    j: usize,
+}
+
+impl QppInverseIterator {
+    /// Get the de-interleaved index and advance the differences to the next index.
+    const fn step(&mut self) -> usize {
+        let pi_inv = self.differences[0];
+        let mut m = 0;
+        while m < self.degree {
+            self.differences[m] = (self.differences[m] + self.differences[m + 1]) % self.k;
+            m += 1;
+        }
+        self.j += 1;
+        pi_inv
+    }
+}
+
+impl Iterator for QppInverseIterator {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.j < self.k {
+            Some(self.step())
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.k - self.j;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for QppInverseIterator {}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::interleavers::lte::LteQpp;
+    use alloc::vec::Vec;
 
     #[test]
     fn iterator_and_pi_are_the_same() {
@@ -100,4 +656,118 @@ pub mod tests {
             assert_eq!(qpp.pi(i), int);
         }
     }
+
+    #[test]
+    fn validate() {
+        assert!(Qpp::try_new(40, 3, 10).is_ok());
+        assert!(LteQpp::block_sizes().all(|k| LteQpp::get(k)
+            .map(|qpp| Qpp::validate(k, qpp.f1, qpp.f2).is_ok())
+            .unwrap()));
+        assert_eq!(Some(QppError::ZeroLength), Qpp::try_new(0, 1, 0).err());
+        assert_eq!(
+            Some(QppError::NotCoprime { factor: 2 }),
+            Qpp::try_new(40, 2, 10).err()
+        );
+        assert_eq!(
+            Some(QppError::MissingFactor { prime: 5 }),
+            Qpp::try_new(40, 3, 4).err()
+        );
+        assert_eq!(
+            Some(QppError::MissingFactor { prime: 7 }),
+            Qpp::try_new(14 * 3, 5, 6).err()
+        );
+    }
+
+    #[test]
+    fn reverse_and_nth() {
+        let qpp = LteQpp::get(6144).unwrap();
+        let forward: Vec<usize> = qpp.iter().collect();
+        let mut backward: Vec<usize> = qpp.iter().rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        let mut iterator = qpp.iter();
+        assert_eq!(Some(forward[1000]), iterator.nth(1000));
+        assert_eq!(Some(forward[1001]), iterator.next());
+        assert_eq!(Some(forward[6143]), iterator.next_back());
+        assert_eq!(5141, iterator.len());
+        assert_eq!(Some(forward[6142]), iterator.nth(5140));
+        assert_eq!(None, iterator.next());
+    }
+
+    #[test]
+    fn contention_free() {
+        assert!(Qpp::new(40, 3, 10).contention_free(3).is_none());
+
+        for k in [40, 1024, 6144] {
+            let qpp = LteQpp::get(k).unwrap();
+            for parallelism in [1, 2, 4, 8] {
+                let m = k / parallelism;
+                let mut iterator = qpp.contention_free(parallelism).unwrap();
+                let mut steps = 0;
+                while let Some(indices) = iterator.next() {
+                    assert_eq!(parallelism, indices.len());
+                    let mut banks = vec![false; parallelism];
+                    for (t, index) in indices.iter().enumerate() {
+                        assert_eq!(qpp.pi(steps + t * m), index.pi);
+                        assert_eq!(indices[0].address, index.address);
+                        assert_eq!(index.pi, index.bank * m + index.address);
+                        assert!(!banks[index.bank]);
+                        banks[index.bank] = true;
+                    }
+                    steps += 1;
+                }
+                assert_eq!(m, steps);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        let qpp = Qpp::try_new(40, 3, 10).unwrap();
+        assert!(qpp.has_fast_inverse());
+        let inverse = qpp.inverse_polynomial().unwrap();
+        assert_eq!(2, inverse.degree());
+        assert_eq!(&[0, 37, 20], inverse.coefficients());
+
+        // Not computed.
+        assert!(Qpp::new(40, 3, 10).inverse_polynomial().is_none());
+
+        // Not a permutation.
+        assert!(Qpp::new(40, 2, 10)
+            .with_inverse()
+            .inverse_polynomial()
+            .is_none());
+
+        for k in LteQpp::block_sizes() {
+            let qpp = LteQpp::get(k).unwrap();
+            let inverse = qpp.inverse_polynomial().unwrap();
+            assert!(inverse.degree() <= 4);
+
+            let mut pi_inv = vec![0; k];
+            for (i, pi) in qpp.iter().enumerate() {
+                pi_inv[pi] = i;
+            }
+            assert!(inverse.iter().eq(pi_inv.iter().copied()));
+            for j in (0..k).step_by(37) {
+                assert_eq!(pi_inv[j], inverse.pi(j));
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_without_polynomial() {
+        fn assert_sync<T: Sync>(_: &T) {}
+
+        let with_inverse = Qpp::new(40, 3, 10).with_inverse();
+        let without = Qpp::new(40, 3, 10);
+        assert_sync(&without);
+        assert!(!without.has_fast_inverse());
+
+        assert!(with_inverse.iter_inv().eq(without.iter_inv()));
+        assert_eq!(40, without.iter_inv().len());
+        for j in 0..40 {
+            assert_eq!(with_inverse.pi_inv(j), without.pi_inv(j));
+        }
+    }
 }
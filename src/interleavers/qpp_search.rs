@@ -0,0 +1,335 @@
+//! Search for QPP interleaver parameters for block lengths outside the 36.212 table.
+use super::{
+    ccsds::modular_inverse,
+    qpp::{gcd, Qpp, QppError},
+};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// The period of the feedback polynomial of the LTE constituent code,
+/// i.e. the smallest separation of a self-terminating weight-2 input.
+const PERIOD: usize = 7;
+
+/// Valid QPP parameters for a block length together with their quality estimates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QppCandidate {
+    /// The block length.
+    pub k: usize,
+    /// The f1 parameter.
+    pub f1: usize,
+    /// The f2 parameter.
+    pub f2: usize,
+    /// The spread, i.e. the minimum over all pairs of indices `i != j` of
+    /// `|i - j| + |pi(i) - pi(j)|`, with the distances taken circularly modulo `k`.
+    pub spread: usize,
+    /// The minimum distance estimate of the LTE Turbo code using the interleaver.
+    /// It is the lowest codeword weight produced by the inputs that are self-terminating
+    /// in both constituent encoders, treating the block as circular, among the weight-2 inputs
+    /// and the weight-4 inputs made of two weight-2 inputs with the same separation in both encoders.
+    /// It is `usize::MAX` if there is no such input.
+    pub distance: usize,
+}
+
+impl QppCandidate {
+    /// Validate the parameters and estimate their spread and minimum distance.
+    pub fn evaluate(k: usize, f1: usize, f2: usize) -> Result<Self, QppError> {
+        Qpp::validate(k, f1, f2)?;
+        Ok(Self::evaluate_valid(k, f1, f2))
+    }
+
+    /// Get the interleaver with the parameters, including its inverse permutation polynomial.
+    pub const fn qpp(&self) -> Qpp {
+        Qpp::new(self.k, self.f1, self.f2).with_inverse()
+    }
+
+    /// Estimate parameters that are known to be valid.
+    fn evaluate_valid(k: usize, f1: usize, f2: usize) -> Self {
+        Self {
+            k,
+            f1,
+            f2,
+            spread: spread(k, f1, f2),
+            distance: distance(k, f1, f2),
+        }
+    }
+
+    /// The ranking order, the best candidate first.
+    /// Candidates are ranked by distance, then by spread, then by the smallest parameters.
+    fn rank(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .cmp(&self.distance)
+            .then(other.spread.cmp(&self.spread))
+            .then(self.f1.cmp(&other.f1))
+            .then(self.f2.cmp(&other.f2))
+    }
+}
+
+/// Enumerate all valid QPP parameters for the block length `k` and get the best `count` of them,
+/// ranked by the minimum distance estimate, then by spread.
+/// Only the parameters `0 < f1, f2 < k` for which `f2` is nonzero are considered,
+/// so nothing is found if `k` has no repeated prime factor.
+/// The search evaluates all `phi(k) (k / rad(k) - 1)` parameters,
+/// where `rad(k)` is the product of the distinct prime factors of `k`,
+/// which takes tens of seconds for the largest LTE block lengths in release builds.
+pub fn search(k: usize, count: usize) -> Vec<QppCandidate> {
+    let mut best: Vec<QppCandidate> = Vec::with_capacity(count + 1);
+    if k == 0 || count == 0 {
+        return best;
+    }
+
+    let radical = radical(k);
+    for f1 in (1..k).filter(|&f1| gcd(f1, k) == 1) {
+        for f2 in (radical..k).step_by(radical) {
+            let candidate = QppCandidate::evaluate_valid(k, f1, f2);
+            let position = best
+                .binary_search_by(|x| x.rank(&candidate))
+                .unwrap_or_else(|x| x);
+            if position < count {
+                best.insert(position, candidate);
+                best.truncate(count);
+            }
+        }
+    }
+
+    best
+}
+
+/// Get the product of the distinct prime factors of `n`.
+fn radical(mut n: usize) -> usize {
+    let mut radical = 1;
+    let mut prime = 2;
+    while n > 1 {
+        if prime * prime > n {
+            prime = n;
+        }
+        if n.is_multiple_of(prime) {
+            radical *= prime;
+            while n.is_multiple_of(prime) {
+                n /= prime;
+            }
+        }
+        prime += 1;
+    }
+    radical
+}
+
+/// Get the index offset `pi(i + t) - pi(i) mod k` for `i = 0` and the step `g` between the offsets for other `i`.
+/// The offset is `f1 t + f2 t^2 + 2 f2 t i mod k`, so it takes the values `c + m g mod k`,
+/// where `g = gcd(2 f2 t, k)`.
+fn offsets(k: usize, f1: usize, f2: usize, t: usize) -> (usize, usize) {
+    let (k64, t64) = (k as u64, t as u64);
+    let c = (f1 as u64 % k64 * t64 + f2 as u64 % k64 * (t64 * t64 % k64)) % k64;
+    let g = gcd((2 * (f2 as u64 % k64) * t64 % k64) as usize, k);
+    (c as usize, g)
+}
+
+/// Get the smallest circular distance from 0 of the values `c + m g mod k`.
+fn circular_distance(c: usize, g: usize) -> usize {
+    let r = c % g;
+    r.min(g - r)
+}
+
+/// Compute the spread.
+/// The indices `i` and `i + t` contribute `t + |pi(i + t) - pi(i)|`,
+/// so only the separations `t` below the current minimum are considered.
+fn spread(k: usize, f1: usize, f2: usize) -> usize {
+    let mut spread = k;
+    let mut t = 1;
+    while t < spread && t <= k / 2 {
+        let (c, g) = offsets(k, f1, f2, t);
+        spread = spread.min(t + circular_distance(c, g));
+        t += 1;
+    }
+    spread
+}
+
+/// Get the parity weight of the LTE constituent encoder for the input `1 + D^(PERIOD periods)`.
+/// The response to the first input bit has weight 4 in every period,
+/// and the second input bit terminates it with weight 2 at the end.
+const fn parity_weight(periods: usize) -> usize {
+    4 * periods + 2
+}
+
+/// Get the smallest `b >= 1` for which `PERIOD b = +-r mod g`, if any.
+fn periods(r: usize, g: usize) -> Option<usize> {
+    let common = gcd(PERIOD, g);
+    if !r.is_multiple_of(common) {
+        return None;
+    }
+
+    let modulus = g / common;
+    let b = (r / common) * modular_inverse(PERIOD / common % modulus, modulus) % modulus;
+    let b = b.min((modulus - b) % modulus);
+    Some(if b == 0 { modulus } else { b })
+}
+
+/// Estimate the minimum distance from the self-terminating inputs, see `QppCandidate::distance`.
+fn distance(k: usize, f1: usize, f2: usize) -> usize {
+    let mut distance = usize::MAX;
+
+    let mut a = 1;
+    while PERIOD * a <= k / 2 && 2 + parity_weight(a) + parity_weight(1) < distance {
+        // The interleaved inputs are separated by `PERIOD b` if `+-PERIOD b = c + m g mod k` for some `m`.
+        let (c, g) = offsets(k, f1, f2, PERIOD * a);
+        if let Some(b) = periods(c % g, g).filter(|&b| PERIOD * b <= k / 2) {
+            distance = distance.min(2 + parity_weight(a) + parity_weight(b));
+        }
+
+        // Two such pairs separated by a multiple `e` of `k / g` have the same interleaved separation,
+        // and they are self-terminating in the second encoder if `pi(i + e) - pi(i)` is a multiple of the period.
+        if 4 + 2 * parity_weight(a) + 2 * parity_weight(1) < distance {
+            for e in (k / g..=k / 2).step_by(k / g) {
+                let (c, g) = offsets(k, f1, f2, e);
+                if let Some(b) = periods(c % g, g).filter(|&b| PERIOD * b <= k / 2) {
+                    distance = distance.min(4 + 2 * parity_weight(a) + 2 * parity_weight(b));
+                }
+            }
+        }
+
+        a += 1;
+    }
+
+    distance
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        encoders::rsc::RscEncoder,
+        interleavers::{lte::LteQpp, Interleaver},
+    };
+
+    /// Compute the spread by comparing all pairs of indices.
+    fn brute_force_spread(k: usize, f1: usize, f2: usize) -> usize {
+        let qpp = Qpp::new(k, f1, f2);
+        let circular = |a: usize, b: usize| {
+            let d = a.abs_diff(b);
+            d.min(k - d)
+        };
+        let mut spread = usize::MAX;
+        for i in 0..k {
+            for j in i + 1..k {
+                spread = spread.min(circular(i, j) + circular(qpp.pi(i), qpp.pi(j)));
+            }
+        }
+        spread
+    }
+
+    #[test]
+    fn spread() {
+        for &(k, f1, f2) in [
+            (40, 3, 10),
+            (48, 7, 12),
+            (104, 7, 26),
+            (72, 5, 6),
+            (100, 3, 10),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                brute_force_spread(k, f1, f2),
+                QppCandidate::evaluate(k, f1, f2).unwrap().spread
+            );
+        }
+    }
+
+    #[test]
+    fn parity_weight() {
+        for periods in 1..16 {
+            let mut encoder = RscEncoder::new();
+            let separation = PERIOD * periods;
+            let weight = (0..=separation)
+                .filter(|&i| encoder.encode_bit(i == 0 || i == separation))
+                .count();
+            assert_eq!(0, encoder.state());
+            assert_eq!(weight, super::parity_weight(periods));
+        }
+    }
+
+    #[test]
+    fn distance() {
+        // Compare with all the pairs of indices separated by multiples of the period,
+        // and all the pairs of such pairs with the same interleaved separation.
+        let circular = |a: usize, b: usize, k: usize| {
+            let d = a.abs_diff(b);
+            d.min(k - d)
+        };
+        let weight = |separation: usize| super::parity_weight(separation / PERIOD);
+        let cases = [
+            (40, 3, 10),
+            (100, 3, 10),
+            (100, 3, 50),
+            (112, 41, 84),
+            (196, 5, 14),
+        ];
+        for &(k, f1, f2) in cases.iter() {
+            let qpp = Qpp::new(k, f1, f2);
+            let offset = |i: usize, t: usize| (k + qpp.pi((i + t) % k) - qpp.pi(i)) % k;
+            let mut distance = usize::MAX;
+            for i in 0..k {
+                for t in (PERIOD..=k / 2).step_by(PERIOD) {
+                    let s = circular(qpp.pi(i), qpp.pi((i + t) % k), k);
+                    if s.is_multiple_of(PERIOD) {
+                        distance = distance.min(2 + weight(t) + weight(s));
+                    }
+                    for e in 1..=k / 2 {
+                        let u = circular(qpp.pi(i), qpp.pi((i + e) % k), k);
+                        if offset(i, t) == offset(i + e, t) && u.is_multiple_of(PERIOD) {
+                            distance = distance.min(4 + 2 * weight(t) + 2 * weight(u));
+                        }
+                    }
+                }
+            }
+            assert_eq!(
+                distance,
+                QppCandidate::evaluate(k, f1, f2).unwrap().distance,
+                "{} {} {}",
+                k,
+                f1,
+                f2
+            );
+        }
+    }
+
+    #[test]
+    fn evaluate() {
+        assert_eq!(
+            Err(QppError::NotCoprime { factor: 2 }),
+            QppCandidate::evaluate(40, 2, 10)
+        );
+
+        let lte = LteQpp::get(6144).unwrap();
+        let candidate = QppCandidate::evaluate(6144, 263, 480).unwrap();
+        assert!(candidate.qpp().iter().eq(lte.iter()));
+        assert_eq!(24, candidate.spread);
+    }
+
+    #[test]
+    fn search() {
+        assert!(super::search(30, 4).is_empty());
+
+        // A block length outside the LTE table.
+        let k = 100;
+        let candidates = super::search(k, 8);
+        assert_eq!(8, candidates.len());
+        for (candidate, next) in candidates.iter().zip(candidates.iter().skip(1)) {
+            assert_ne!(Ordering::Greater, candidate.rank(next));
+        }
+        for candidate in candidates.iter() {
+            assert_eq!(k, candidate.k);
+            assert_eq!(
+                Ok(*candidate),
+                QppCandidate::evaluate(k, candidate.f1, candidate.f2)
+            );
+            let qpp = candidate.qpp();
+            assert!(qpp.inverse_polynomial().is_some());
+            assert!(qpp.iter_inv().enumerate().all(|(j, i)| qpp.pi(i) == j));
+        }
+
+        // The LTE parameters are the best for the smallest block length.
+        let best = super::search(40, 1)[0];
+        assert_eq!(Ok(best), QppCandidate::evaluate(40, 3, 10));
+    }
+}
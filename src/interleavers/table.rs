@@ -0,0 +1,129 @@
+use super::Interleaver;
+use alloc::vec::Vec;
+use core::{iter::Copied, slice};
+
+/// Interleaver defined by a table of the interleaved indices.
+/// The inverse table is computed when the interleaver is created, so that `pi_inv` is a lookup.
+#[derive(Clone, Debug)]
+pub struct TableInterleaver {
+    /// The interleaved indices.
+    pi: Vec<usize>,
+    /// The de-interleaved indices.
+    pi_inv: Vec<usize>,
+}
+
+/// The reason why a table is not a permutation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableError {
+    /// The `value` at `index` is not less than the table length.
+    OutOfRange { index: usize, value: usize },
+    /// The `value` at `index` also appears earlier in the table.
+    Duplicate { index: usize, value: usize },
+}
+
+impl TableInterleaver {
+    /// Create an interleaver from the interleaved indices `pi`,
+    /// which must contain every index from 0 to `pi.len() - 1` exactly once.
+    pub fn new(pi: &[usize]) -> Result<Self, TableError> {
+        const UNSET: usize = usize::MAX;
+
+        let mut pi_inv = vec![UNSET; pi.len()];
+        for (index, &value) in pi.iter().enumerate() {
+            let inverse = pi_inv
+                .get_mut(value)
+                .ok_or(TableError::OutOfRange { index, value })?;
+            if *inverse != UNSET {
+                return Err(TableError::Duplicate { index, value });
+            }
+            *inverse = index;
+        }
+
+        Ok(Self {
+            pi: pi.to_vec(),
+            pi_inv,
+        })
+    }
+
+    /// Create an interleaver from any interleaver, storing its tables.
+    pub fn from_interleaver<I: Interleaver>(interleaver: &I) -> Self {
+        let pi: Vec<usize> = interleaver.iter().collect();
+        let mut pi_inv = vec![0; pi.len()];
+        for (index, &value) in pi.iter().enumerate() {
+            pi_inv[value] = index;
+        }
+        Self { pi, pi_inv }
+    }
+
+    /// Get the interleaved indices.
+    pub fn as_slice(&self) -> &[usize] {
+        &self.pi
+    }
+}
+
+impl Interleaver for TableInterleaver {
+    type Iter<'a> = Copied<slice::Iter<'a, usize>>;
+
+    type IterInv<'a> = Copied<slice::Iter<'a, usize>>;
+
+    fn len(&self) -> usize {
+        self.pi.len()
+    }
+
+    fn pi(&self, i: usize) -> usize {
+        self.pi[i]
+    }
+
+    fn pi_inv(&self, j: usize) -> usize {
+        self.pi_inv[j]
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.pi.iter().copied()
+    }
+
+    fn iter_inv(&self) -> Self::IterInv<'_> {
+        self.pi_inv.iter().copied()
+    }
+}
+
+impl<'a> IntoIterator for &'a TableInterleaver {
+    type Item = usize;
+
+    type IntoIter = Copied<slice::Iter<'a, usize>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pi.iter().copied()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::interleavers::qpp::Qpp;
+
+    #[test]
+    fn validation() {
+        assert!(TableInterleaver::new(&[]).is_ok());
+        assert_eq!(
+            Err(TableError::OutOfRange { index: 1, value: 3 }),
+            TableInterleaver::new(&[0, 3, 1]).map(|_| ())
+        );
+        assert_eq!(
+            Err(TableError::Duplicate { index: 2, value: 0 }),
+            TableInterleaver::new(&[0, 2, 0]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn pi_inv() {
+        let table = TableInterleaver::new(&[2, 0, 3, 1]).unwrap();
+        assert_eq!(4, table.len());
+        assert_eq!(3, table.pi(2));
+        assert_eq!(vec![1, 3, 0, 2], table.iter_inv().collect::<Vec<_>>());
+
+        let qpp = Qpp::new(40, 3, 10);
+        let table = TableInterleaver::from_interleaver(&qpp);
+        assert!(table.iter().eq(qpp.iter()));
+        assert!(table.iter_inv().eq(qpp.iter_inv()));
+    }
+}
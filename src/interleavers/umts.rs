@@ -0,0 +1,272 @@
+use super::{qpp::gcd, Interleaver};
+use alloc::vec::Vec;
+use core::{
+    iter::{once, successors},
+    slice,
+};
+
+/// UMTS prime interleaver, the Turbo code internal interleaver of 25.212 4.2.3.2.3.
+/// The input is written row by row into a matrix of `R` rows and `C` columns,
+/// where the columns of each row are permuted using the powers of a primitive root of a prime `p`,
+/// and the rows are permuted using a fixed pattern.
+/// The output is read column by column, pruning the positions beyond the block length.
+/// The permutation and its inverse are computed once when the interleaver is created.
+#[derive(Clone, Debug)]
+pub struct UmtsInterleaver {
+    /// The interleaved indices.
+    pi: Vec<u16>,
+    /// The de-interleaved indices.
+    pi_inv: Vec<u16>,
+}
+
+/// The inter-row permutation pattern for `R = 20` and `2281 <= K <= 2480` or `3161 <= K <= 3210`.
+const PATTERN_A: [u8; 20] = [
+    19, 9, 14, 4, 0, 2, 5, 7, 12, 18, 16, 13, 17, 15, 3, 1, 6, 11, 8, 10,
+];
+
+/// The inter-row permutation pattern for `R = 20` and the other block lengths.
+const PATTERN_B: [u8; 20] = [
+    19, 9, 14, 4, 0, 2, 5, 7, 12, 18, 10, 8, 13, 17, 3, 1, 16, 6, 15, 11,
+];
+
+const fn is_prime(n: usize) -> bool {
+    if n < 2 {
+        return false;
+    }
+    let mut d = 2;
+    while d * d <= n {
+        if n.is_multiple_of(d) {
+            return false;
+        }
+        d += 1;
+    }
+    true
+}
+
+/// Get the smallest primitive root of the prime `p`, see 25.212 Table 2.
+fn primitive_root(p: usize) -> usize {
+    let order = p - 1;
+    let factors: Vec<usize> = (2..=order)
+        .filter(|&q| order.is_multiple_of(q) && is_prime(q))
+        .collect();
+    let pow = |base: usize, exponent: usize| (0..exponent).fold(1, |x, _| x * base % p);
+
+    (2..p)
+        .find(|&v| factors.iter().all(|&q| pow(v, order / q) != 1))
+        .unwrap()
+}
+
+impl UmtsInterleaver {
+    /// Get an interleaver for the block length `k` (in bits), which must be from 40 to 5114.
+    pub fn get(k: usize) -> Option<Self> {
+        if !(40..=5114).contains(&k) {
+            return None;
+        }
+
+        // The number of rows and the inter-row permutation pattern, see 25.212 Table 3.
+        let pattern: &[u8] = match k {
+            40..=159 => &[4, 3, 2, 1, 0],
+            160..=200 | 481..=530 => &[9, 8, 7, 6, 5, 4, 3, 2, 1, 0],
+            2281..=2480 | 3161..=3210 => &PATTERN_A,
+            _ => &PATTERN_B,
+        };
+        let rows = pattern.len();
+
+        // The prime and the number of columns.
+        let (p, columns) = if (481..=530).contains(&k) {
+            (53, 53)
+        } else {
+            let p = (2..).find(|&p| is_prime(p) && k <= rows * (p + 1)).unwrap();
+            let columns = if k <= rows * (p - 1) {
+                p - 1
+            } else if k <= rows * p {
+                p
+            } else {
+                p + 1
+            };
+            (p, columns)
+        };
+
+        // The base sequence for the intra-row permutation.
+        let v = primitive_root(p);
+        let s: Vec<usize> = successors(Some(1), |&x| Some(x * v % p))
+            .take(p - 1)
+            .collect();
+
+        // The prime integers for each row, in the order of the original rows.
+        let q = once(1).chain((7..).filter(|&q| is_prime(q) && gcd(q, p - 1) == 1));
+        let mut r = vec![0; rows];
+        for (&row, q) in pattern.iter().zip(q) {
+            r[row as usize] = q;
+        }
+
+        // The intra-row permutation for each original row.
+        let u: Vec<Vec<usize>> = (0..rows)
+            .map(|row| {
+                let mut u: Vec<usize> = (0..p - 1).map(|j| s[(j * r[row]) % (p - 1)]).collect();
+                if columns == p - 1 {
+                    for x in u.iter_mut() {
+                        *x -= 1;
+                    }
+                } else {
+                    u.push(0);
+                    if columns == p + 1 {
+                        u.push(p);
+                        if row == rows - 1 && k == rows * columns {
+                            u.swap(0, p);
+                        }
+                    }
+                }
+                u
+            })
+            .collect();
+
+        // Read the permuted matrix column by column, pruning the dummy positions.
+        let pi: Vec<u16> = (0..columns)
+            .flat_map(|j| {
+                let u = &u;
+                pattern
+                    .iter()
+                    .map(move |&row| row as usize * columns + u[row as usize][j])
+            })
+            .filter(|&index| index < k)
+            .map(|index| index as u16)
+            .collect();
+
+        let mut pi_inv = vec![0; k];
+        for (i, &pi) in pi.iter().enumerate() {
+            pi_inv[pi as usize] = i as u16;
+        }
+
+        Some(Self { pi, pi_inv })
+    }
+
+    /// The block length.
+    pub fn k(&self) -> usize {
+        self.pi.len()
+    }
+
+    /// Get the interleaved index.
+    pub fn pi(&self, i: usize) -> usize {
+        self.pi[i] as usize
+    }
+
+    /// Get an iterator that produces the permuted sequence.
+    pub fn iter(&self) -> UmtsIterator<'_> {
+        self.into_iter()
+    }
+}
+
+pub type UmtsIterator<'a> = core::iter::Map<slice::Iter<'a, u16>, fn(&u16) -> usize>;
+
+impl<'a> IntoIterator for &'a UmtsInterleaver {
+    type Item = usize;
+
+    type IntoIter = UmtsIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pi.iter().map(|&x| x as usize)
+    }
+}
+
+impl Interleaver for UmtsInterleaver {
+    type Iter<'a> = UmtsIterator<'a>;
+
+    type IterInv<'a> = UmtsIterator<'a>;
+
+    fn len(&self) -> usize {
+        self.k()
+    }
+
+    fn pi(&self, i: usize) -> usize {
+        UmtsInterleaver::pi(self, i)
+    }
+
+    fn pi_inv(&self, j: usize) -> usize {
+        self.pi_inv[j] as usize
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        UmtsInterleaver::iter(self)
+    }
+
+    fn iter_inv(&self) -> Self::IterInv<'_> {
+        self.pi_inv.iter().map(|&x| x as usize)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        encoders::lte::LteTurboEncoder, trellises::lte::UmtsTrellis, Llr, TurboCodeword,
+        TurboDecoder,
+    };
+    use streaming_iterator::StreamingIterator;
+
+    #[test]
+    fn primitive_roots() {
+        assert_eq!(3, primitive_root(7));
+        assert_eq!(2, primitive_root(53));
+        assert_eq!(5, primitive_root(97));
+        assert_eq!(3, primitive_root(257));
+    }
+
+    #[test]
+    fn k40() {
+        // R = 5, p = 7, C = 8 with the exchange in the last row as K = R * C.
+        let interleaver = UmtsInterleaver::get(40).unwrap();
+        assert_eq!(
+            vec![39, 25, 17, 9, 1, 35, 27, 21, 11, 5],
+            interleaver.iter().take(10).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn is_permutation() {
+        assert!(UmtsInterleaver::get(39).is_none());
+        assert!(UmtsInterleaver::get(5115).is_none());
+
+        for k in 40..=5114 {
+            let interleaver = UmtsInterleaver::get(k).unwrap();
+            let mut seen = vec![false; k];
+            for pi in &interleaver {
+                assert!(!seen[pi]);
+                seen[pi] = true;
+            }
+            assert!(seen.iter().all(|&x| x));
+            assert!(Interleaver::iter_inv(&interleaver)
+                .enumerate()
+                .all(|(j, i)| interleaver.pi(i) == j));
+        }
+    }
+
+    #[test]
+    fn encode_decode() {
+        let llrs = |bits: &[bool]| -> Vec<Llr> {
+            bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+        };
+
+        for &k in [40, 500, 2300].iter() {
+            let input: Vec<bool> = (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+            let interleaver = UmtsInterleaver::get(k).unwrap();
+            let codeword = LteTurboEncoder.encode(&input, &interleaver);
+            let mut received = TurboCodeword {
+                systematic: llrs(&codeword.systematic),
+                first_systematic_termination: llrs(&codeword.first_systematic_termination),
+                first_parity: llrs(&codeword.first_parity),
+                second_systematic_termination: llrs(&codeword.second_systematic_termination),
+                second_parity: llrs(&codeword.second_parity),
+            };
+            for i in (0..k).step_by(9) {
+                received.systematic[i] = Llr(-received.systematic[i].0);
+            }
+
+            let mut turbo = TurboDecoder::new(UmtsTrellis);
+            let mut iterator = turbo.decode_codeword(&received, &interleaver);
+            let l_app = iterator.nth(2).unwrap();
+            let decoded: Vec<bool> = l_app.iter().map(|x| x.hard()).collect();
+            assert_eq!(input, decoded);
+        }
+    }
+}
@@ -1,14 +1,23 @@
-#![feature(asm)]
-#![feature(const_fn_union)]
 #![cfg_attr(not(feature = "std"), no_std)]
 
 #[macro_use]
 extern crate alloc;
 
 mod bcjr;
+mod codeword;
+pub mod crc;
+pub mod demapper;
+mod duobinary;
 pub mod dword;
+pub mod encoders;
+pub mod harq;
 pub mod interleavers;
 mod llr;
+pub mod quantizer;
+pub mod rate_matching;
+pub mod scrambling;
+pub mod segmentation;
+pub mod stopping;
 pub mod trellises;
 
 #[cfg(target_tests)]
@@ -21,4 +30,10 @@ pub mod turbo;
 #[cfg(not(target_tests))]
 mod turbo;
 
-pub use self::{bcjr::BcjrDecoder, llr::Llr, turbo::TurboDecoder};
+pub use self::{
+    bcjr::{BcjrDecoder, BoundaryMetrics},
+    codeword::{DuoBinaryCodeword, TurboCodeword},
+    duobinary::DuoBinaryTurboDecoder,
+    llr::{Llr, SymbolLlr},
+    turbo::{ExtrinsicScale, TurboDecoder},
+};
@@ -6,7 +6,7 @@ pub struct Llr(pub i8);
 #[macro_export]
 macro_rules! llr_vec {
     ($($llr:expr),+) => {
-        vec![$(crate::Llr($llr)),+]
+        vec![$($crate::Llr($llr)),+]
     };
     ($($llr:expr),+,) => {
         llr_vec!($($llr),+)
@@ -17,18 +17,115 @@ impl Llr {
     /// The equiprobable value.
     pub const ZERO: Llr = Llr(0);
 
+    pub fn saturating_add(self: Llr, rhs: Llr) -> Llr {
+        Llr(self.0.saturating_add(rhs.0))
+    }
+
     pub fn saturating_sub(self: Llr, rhs: Llr) -> Llr {
         Llr(self.0.saturating_sub(rhs.0))
     }
 
+    /// Flip the sign, saturating at the bounds.
+    pub fn saturating_neg(self: Llr) -> Llr {
+        Llr(self.0.saturating_neg())
+    }
+
     /// Make a hard decode decision.
     pub fn hard(self) -> bool {
         self.0 > 0
     }
 }
 
-impl Into<Llr> for i8 {
-    fn into(self) -> Llr {
-        Llr(self)
+impl From<i8> for Llr {
+    fn from(value: i8) -> Self {
+        Llr(value)
+    }
+}
+
+/// Symbol log-likelihoods of a couple of bits `(A, B)`, indexed by the symbol `2A + B`.
+/// The values are relative to the symbol `(0, 0)`, which therefore always has the value 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SymbolLlr(pub [i8; 4]);
+
+impl SymbolLlr {
+    /// The equiprobable value.
+    pub const ZERO: SymbolLlr = SymbolLlr([0; 4]);
+
+    /// The symbol log-likelihoods of two independent bits with the llr's `a` and `b`.
+    pub fn from_bits(a: Llr, b: Llr) -> Self {
+        Self([0, b.0, a.0, a.0.saturating_add(b.0)])
+    }
+
+    /// Create from the log-likelihoods `metrics` of each symbol, which need not be relative to `(0, 0)`.
+    /// The values are saturated.
+    pub fn from_metrics(metrics: [i32; 4]) -> Self {
+        let relative =
+            |metric: i32| (metric - metrics[0]).clamp(i8::MIN as i32, i8::MAX as i32) as i8;
+        Self([
+            0,
+            relative(metrics[1]),
+            relative(metrics[2]),
+            relative(metrics[3]),
+        ])
+    }
+
+    /// The llr's of the bits `A` and `B` using the max-log approximation.
+    pub fn to_bits(self) -> (Llr, Llr) {
+        let [s00, s01, s10, s11] = self.0;
+        let a = s10.max(s11).saturating_sub(s00.max(s01));
+        let b = s01.max(s11).saturating_sub(s00.max(s10));
+        (Llr(a), Llr(b))
+    }
+
+    pub fn saturating_add(self, rhs: SymbolLlr) -> SymbolLlr {
+        let mut sum = self;
+        for (x, &y) in sum.0.iter_mut().zip(&rhs.0) {
+            *x = x.saturating_add(y);
+        }
+        sum
+    }
+
+    pub fn saturating_sub(self, rhs: SymbolLlr) -> SymbolLlr {
+        let mut difference = self;
+        for (x, &y) in difference.0.iter_mut().zip(&rhs.0) {
+            *x = x.saturating_sub(y);
+        }
+        difference
+    }
+
+    /// Exchange the bits `A` and `B`, i.e. the values of the symbols `(0, 1)` and `(1, 0)`.
+    pub fn swapped(self) -> SymbolLlr {
+        let [s00, s01, s10, s11] = self.0;
+        Self([s00, s10, s01, s11])
+    }
+
+    /// Make a hard decode decision of the couple `(A, B)`.
+    pub fn hard(self) -> (bool, bool) {
+        let mut symbol = 0;
+        for (index, &value) in self.0.iter().enumerate() {
+            if value > self.0[symbol] {
+                symbol = index;
+            }
+        }
+        (symbol & 2 != 0, symbol & 1 != 0)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn symbol_llr() {
+        let symbol = SymbolLlr::from_bits(Llr(5), Llr(-3));
+        assert_eq!(SymbolLlr([0, -3, 5, 2]), symbol);
+        assert_eq!((Llr(5), Llr(-3)), symbol.to_bits());
+        assert_eq!((true, false), symbol.hard());
+        assert_eq!((false, true), symbol.swapped().hard());
+
+        assert_eq!(
+            SymbolLlr([0, -128, 127, 1]),
+            SymbolLlr::from_metrics([10, -200, 300, 11])
+        );
     }
 }
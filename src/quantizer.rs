@@ -0,0 +1,200 @@
+//! Quantization of Soft Values into Llr's
+use crate::Llr;
+use alloc::vec::Vec;
+
+/// The fraction of the full scale that the mean magnitude is mapped to when scaling automatically,
+/// leaving headroom for the larger magnitudes.
+const AUTO_SCALE_FRACTION: f32 = 0.25;
+
+/// The number of fractional bits in the fixed-point gain used for integer samples.
+const GAIN_FRACTIONAL_BITS: u32 = 16;
+
+/// A soft value that can be quantized.
+pub trait Sample: Copy {
+    /// Scale and round the sample without saturation.
+    fn scaled(self, quantizer: &Quantizer) -> i32;
+
+    /// The magnitude of the sample.
+    fn magnitude(self) -> f32;
+}
+
+impl Sample for f32 {
+    fn scaled(self, quantizer: &Quantizer) -> i32 {
+        let x = self * quantizer.scale;
+        // The cast saturates and rounds towards zero.
+        (if x >= 0.0 { x + 0.5 } else { x - 0.5 }) as i32
+    }
+
+    fn magnitude(self) -> f32 {
+        if self >= 0.0 {
+            self
+        } else {
+            -self
+        }
+    }
+}
+
+impl Sample for i16 {
+    fn scaled(self, quantizer: &Quantizer) -> i32 {
+        // Round the magnitude to keep the rounding symmetric around zero.
+        let x = self as i64 * quantizer.gain as i64;
+        let magnitude = (x.abs() + (1 << (GAIN_FRACTIONAL_BITS - 1))) >> GAIN_FRACTIONAL_BITS;
+        (x.signum() * magnitude).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+
+    fn magnitude(self) -> f32 {
+        self.unsigned_abs() as f32
+    }
+}
+
+/// Quantizer that maps soft values to llr's of a limited bit width.
+/// Using fewer than 8 bits leaves headroom for the decoder when it adds the a-priori and channel values.
+#[derive(Clone, Debug)]
+pub struct Quantizer {
+    /// The largest magnitude of the quantized values.
+    max: i8,
+    /// The factor that the samples are multiplied with.
+    scale: f32,
+    /// The scale in fixed-point with `GAIN_FRACTIONAL_BITS` fractional bits.
+    gain: i32,
+}
+
+impl Quantizer {
+    /// Create a quantizer producing llr's of `bits` bits, i.e. in the range `±(2^(bits-1) - 1)`.
+    /// The samples are not scaled.
+    pub fn new(bits: u32) -> Self {
+        assert!((2..=8).contains(&bits));
+        Self {
+            max: ((1i32 << (bits - 1)) - 1) as i8,
+            scale: 1.0,
+            gain: 1 << GAIN_FRACTIONAL_BITS,
+        }
+    }
+
+    /// Set the factor that the samples are multiplied with before rounding.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        assert!(scale > 0.0);
+        let gain = scale * (1 << GAIN_FRACTIONAL_BITS) as f32;
+        assert!(gain < i32::MAX as f32);
+        self.scale = scale;
+        self.gain = (gain + 0.5) as i32;
+        self
+    }
+
+    /// Set the scale from the statistics of a block of `samples`,
+    /// such that their mean magnitude maps to a quarter of the full scale.
+    /// Non-finite samples are ignored, and the scale is unchanged if all other samples are zero.
+    pub fn with_auto_scale<S: Sample>(self, samples: &[S]) -> Self {
+        let (sum, count) = samples
+            .iter()
+            .map(|x| x.magnitude())
+            .filter(|x| x.is_finite())
+            .fold((0.0f32, 0usize), |(sum, count), x| (sum + x, count + 1));
+        if sum > 0.0 && sum.is_finite() {
+            let mean = sum / count as f32;
+            let target = AUTO_SCALE_FRACTION * self.max as f32;
+            self.with_scale(target / mean)
+        } else {
+            self
+        }
+    }
+
+    /// The largest magnitude of the quantized values.
+    pub const fn max(&self) -> i8 {
+        self.max
+    }
+
+    /// The factor that the samples are multiplied with.
+    pub const fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Quantize a single `sample`, saturating at the full scale.
+    pub fn quantize_sample<S: Sample>(&self, sample: S) -> Llr {
+        let max = self.max as i32;
+        Llr(sample.scaled(self).clamp(-max, max) as i8)
+    }
+
+    /// Quantize `samples` into `llrs`, which must have the same length.
+    pub fn quantize_into<S: Sample>(&self, samples: &[S], llrs: &mut [Llr]) {
+        assert_eq!(samples.len(), llrs.len());
+        for (&sample, llr) in samples.iter().zip(llrs.iter_mut()) {
+            *llr = self.quantize_sample(sample);
+        }
+    }
+
+    /// Quantize `samples` into a new vector of llr's.
+    pub fn quantize<S: Sample>(&self, samples: &[S]) -> Vec<Llr> {
+        samples.iter().map(|&x| self.quantize_sample(x)).collect()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::llr_vec;
+
+    #[test]
+    fn quantize_f32() {
+        let quantizer = Quantizer::new(6).with_scale(2.0);
+        assert_eq!(31, quantizer.max());
+        assert_eq!(
+            llr_vec![0, 1, -1, 3, -3, 31, -31],
+            quantizer.quantize(&[0.1f32, 0.3, -0.3, 1.4, -1.6, 16.0, -1e9])
+        );
+    }
+
+    #[test]
+    fn quantize_i16() {
+        let quantizer = Quantizer::new(4).with_scale(0.25);
+        assert_eq!(7, quantizer.max());
+        assert_eq!(
+            llr_vec![0, 1, -1, 5, -5, 7, -7],
+            quantizer.quantize(&[1i16, 2, -2, 19, -21, 100, i16::MIN])
+        );
+        assert_eq!(Llr(127), Quantizer::new(8).quantize_sample(i16::MAX));
+    }
+
+    #[test]
+    fn auto_scale() {
+        let samples: Vec<i16> = (0..100)
+            .map(|i| if i % 2 == 0 { 400 } else { -1200 })
+            .collect();
+        let quantizer = Quantizer::new(5).with_auto_scale(&samples);
+        assert_eq!(15, quantizer.max());
+
+        // The mean magnitude of 800 maps to 15/4.
+        let llrs = quantizer.quantize(&samples);
+        assert_eq!(Llr(2), llrs[0]);
+        assert_eq!(Llr(-6), llrs[1]);
+
+        let unchanged = Quantizer::new(5).with_auto_scale(&[0.0f32; 4]);
+        assert_eq!(1.0, unchanged.scale());
+    }
+
+    #[test]
+    fn auto_scale_non_finite() {
+        let samples = [2.0f32, f32::INFINITY, -2.0, f32::NAN, f32::NEG_INFINITY];
+        let quantizer = Quantizer::new(5).with_auto_scale(&samples);
+        assert_eq!(15.0 / 4.0 / 2.0, quantizer.scale());
+        assert_eq!(llr_vec![4, 15, -4, 0, -15], quantizer.quantize(&samples));
+
+        let unchanged = Quantizer::new(5).with_auto_scale(&[0.0f32, f32::INFINITY]);
+        assert_eq!(1.0, unchanged.scale());
+    }
+
+    #[test]
+    fn rounded_gain() {
+        // The gain of about 3.8 in fixed-point is rounded to 4 rather than truncated to 3.
+        let samples: Vec<i16> = (0..100)
+            .map(|i| if i % 2 == 0 { 30000 } else { -30000 })
+            .collect();
+        let quantizer = Quantizer::new(4).with_auto_scale(&samples);
+        assert_eq!(4, quantizer.gain);
+
+        let floats: Vec<f32> = samples.iter().map(|&x| x as f32).collect();
+        let llrs = quantizer.quantize(&samples);
+        assert_eq!(quantizer.quantize(&floats), llrs);
+        assert_eq!(Llr(2), llrs[0]);
+    }
+}
@@ -0,0 +1,357 @@
+//! LTE Rate Matching for Turbo coded transport channels, see 36.212 5.1.4.1.
+use crate::{Llr, TurboCodeword};
+use alloc::vec::Vec;
+
+/// The number of columns in the sub-block interleaver.
+const COLUMNS: usize = 32;
+
+/// The inter-column permutation pattern, see 36.212 table 5.1.4-1.
+const COLUMN_PERMUTATION: [u8; COLUMNS] = [
+    0, 16, 8, 24, 4, 20, 12, 28, 2, 18, 10, 26, 6, 22, 14, 30, 1, 17, 9, 25, 5, 21, 13, 29, 3, 19,
+    11, 27, 7, 23, 15, 31,
+];
+
+/// Sub-block interleaver, see 36.212 5.1.4.1.1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubBlockInterleaver {
+    /// The number of rows `R`.
+    rows: usize,
+    /// The number of dummy bits `N_D` padded in front of the input stream.
+    dummies: usize,
+}
+
+impl SubBlockInterleaver {
+    /// Create a sub-block interleaver for streams of length `d`.
+    pub const fn new(d: usize) -> Self {
+        let rows = d.div_ceil(COLUMNS);
+        Self {
+            rows,
+            dummies: rows * COLUMNS - d,
+        }
+    }
+
+    /// The interleaver output length `K_Π`.
+    pub const fn len(&self) -> usize {
+        self.rows * COLUMNS
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// The input stream length `D`.
+    pub const fn d(&self) -> usize {
+        self.len() - self.dummies
+    }
+
+    /// The number of rows `R`.
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the index in the input stream `stream` (0, 1 or 2) that is interleaved to output position `k`.
+    /// `None` is returned if the output is a dummy bit.
+    pub const fn pi(&self, stream: usize, k: usize) -> Option<usize> {
+        let column = COLUMN_PERMUTATION[k / self.rows] as usize;
+        let y = if stream == 2 {
+            (column + COLUMNS * (k % self.rows) + 1) % self.len()
+        } else {
+            (k % self.rows) * COLUMNS + column
+        };
+
+        if y >= self.dummies {
+            Some(y - self.dummies)
+        } else {
+            None
+        }
+    }
+}
+
+/// Rate matcher for a Turbo coded block of length `K`, see 36.212 5.1.4.1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateMatcher {
+    /// The sub-block interleaver for streams of length `K+4`.
+    interleaver: SubBlockInterleaver,
+    /// The circular buffer length `N_cb`.
+    ncb: usize,
+    /// The number of filler bits `F` at the start of the block.
+    fillers: usize,
+}
+
+impl RateMatcher {
+    /// Create a rate matcher for the block length `k` (in bits) using the full circular buffer,
+    /// i.e. `N_cb = K_w` as for the UL-SCH.
+    pub const fn new(k: usize) -> Self {
+        let interleaver = SubBlockInterleaver::new(k + 4);
+        Self {
+            interleaver,
+            ncb: 3 * interleaver.len(),
+            fillers: 0,
+        }
+    }
+
+    /// Limit the circular buffer length `N_cb`, e.g. to `floor(N_IR / C)` as for the DL-SCH.
+    pub fn with_ncb(self, ncb: usize) -> Self {
+        assert!(ncb > 0, "The circular buffer must not be empty.");
+        Self {
+            ncb: ncb.min(3 * self.interleaver.len()),
+            ..self
+        }
+    }
+
+    /// Set the number of filler bits `F` at the start of the block.
+    /// The filler bits in the `d0` and `d1` streams are not transmitted.
+    pub fn with_fillers(self, fillers: usize) -> Self {
+        Self { fillers, ..self }
+    }
+
+    /// The block length `K`.
+    pub const fn k(&self) -> usize {
+        self.interleaver.d() - 4
+    }
+
+    /// The circular buffer length `N_cb`.
+    pub const fn ncb(&self) -> usize {
+        self.ncb
+    }
+
+    /// Get the circular buffer start position `k_0` for the redundancy version `rv` (0, 1, 2 or 3).
+    pub const fn k0(&self, rv: u8) -> usize {
+        let rows = self.interleaver.rows();
+        let columns = self.ncb.div_ceil(8 * rows);
+        rows * (2 * columns * rv as usize + 2)
+    }
+
+    /// Get the `(stream, index)` `d` stream position for the circular buffer position `k`.
+    /// `None` is returned if the position holds a dummy or a filler bit.
+    pub const fn position(&self, k: usize) -> Option<(usize, usize)> {
+        let kpi = self.interleaver.len();
+        let (stream, k) = if k < kpi {
+            (0, k)
+        } else {
+            (1 + (k - kpi) % 2, (k - kpi) / 2)
+        };
+
+        match self.interleaver.pi(stream, k) {
+            Some(index) if stream == 2 || index >= self.fillers => Some((stream, index)),
+            _ => None,
+        }
+    }
+
+    /// Get an iterator over the `d` stream positions for the `e` transmitted bits
+    /// when using the redundancy version `rv`.
+    /// The circular buffer must hold a position that is not a dummy or a filler bit if `e > 0`.
+    pub fn positions(&self, e: usize, rv: u8) -> impl Iterator<Item = (usize, usize)> + '_ {
+        assert!(
+            e == 0 || (0..self.ncb).any(|k| self.position(k).is_some()),
+            "The circular buffer holds only dummy and filler bits."
+        );
+
+        let k0 = self.k0(rv);
+        (0..self.ncb)
+            .cycle()
+            .skip(k0 % self.ncb)
+            .filter_map(move |k| self.position(k))
+            .take(e)
+    }
+
+    /// Select the `e` bits to transmit from `codeword` for the redundancy version `rv`.
+    pub fn rate_match<T: Copy>(&self, codeword: &TurboCodeword<T>, e: usize, rv: u8) -> Vec<T> {
+        assert_eq!(
+            self.interleaver.len(),
+            SubBlockInterleaver::new(codeword.len() + 4).len()
+        );
+
+        self.positions(e, rv)
+            .map(|(stream, index)| codeword.d(stream, index))
+            .collect()
+    }
+
+    /// Scatter the `received` soft values for the redundancy version `rv` into `codeword`.
+    /// The values are combined with those already present in `codeword`.
+    pub fn dematch_into(&self, received: &[Llr], rv: u8, codeword: &mut TurboCodeword<Llr>) {
+        assert_eq!(
+            self.interleaver.len(),
+            SubBlockInterleaver::new(codeword.len() + 4).len()
+        );
+
+        for ((stream, index), &llr) in self.positions(received.len(), rv).zip(received) {
+            let value = codeword.d_mut(stream, index);
+            *value = value.saturating_add(llr);
+        }
+    }
+
+    /// Create a codeword of length `k` from the `received` soft values for the redundancy version `rv`.
+    /// Positions that were not received are `Llr::ZERO`, and repeated positions are combined.
+    pub fn dematch(&self, k: usize, received: &[Llr], rv: u8) -> TurboCodeword<Llr> {
+        let mut codeword = TurboCodeword::from_elem(k, Llr::ZERO);
+        self.dematch_into(received, rv, &mut codeword);
+        codeword
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        encoders::lte::LteTurboEncoder, interleavers::lte::LteQpp, trellises::lte::UmtsTrellis,
+        TurboDecoder,
+    };
+    use streaming_iterator::StreamingIterator;
+
+    fn llrs(bits: &[bool]) -> Vec<Llr> {
+        bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+    }
+
+    #[test]
+    fn sub_block_interleaver_is_a_permutation() {
+        for d in [44, 64, 68, 1028, 6148].iter().copied() {
+            let interleaver = SubBlockInterleaver::new(d);
+            assert_eq!(0, interleaver.len() % COLUMNS);
+            assert!(interleaver.len() >= d);
+
+            for stream in 0..3 {
+                let mut seen = vec![false; d];
+                for k in 0..interleaver.len() {
+                    if let Some(index) = interleaver.pi(stream, k) {
+                        assert!(!seen[index]);
+                        seen[index] = true;
+                    }
+                }
+                assert!(seen.iter().all(|&x| x));
+            }
+        }
+    }
+
+    #[test]
+    fn sub_block_interleaver_positions() {
+        // D = 44 gives R = 2 and 20 dummy bits.
+        let interleaver = SubBlockInterleaver::new(44);
+        assert_eq!(2, interleaver.rows());
+        assert_eq!(64, interleaver.len());
+
+        // Column 0 contains the dummy at y=0, and the bit at y=32.
+        assert_eq!(None, interleaver.pi(0, 0));
+        assert_eq!(Some(12), interleaver.pi(0, 1));
+        // Column 16 contains the dummy at y=16, and the bit at y=48.
+        assert_eq!(None, interleaver.pi(0, 2));
+        assert_eq!(Some(28), interleaver.pi(0, 3));
+        // The third stream is shifted by one.
+        assert_eq!(None, interleaver.pi(2, 0));
+        assert_eq!(Some(13), interleaver.pi(2, 1));
+    }
+
+    #[test]
+    fn k0() {
+        let rate_matcher = RateMatcher::new(40);
+        assert_eq!(192, rate_matcher.ncb());
+        assert_eq!(4, rate_matcher.k0(0));
+        assert_eq!(4 + 2 * 2 * 12, rate_matcher.k0(1));
+        assert_eq!(4 + 2 * 2 * 12 * 2, rate_matcher.k0(2));
+        assert_eq!(4 + 2 * 2 * 12 * 3, rate_matcher.k0(3));
+    }
+
+    #[test]
+    fn match_dematch_all_bits() {
+        let k = 40;
+        let input: Vec<bool> = (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let codeword = LteTurboEncoder.encode(&input, LteQpp::get(k).unwrap());
+        let rate_matcher = RateMatcher::new(k);
+
+        for rv in 0..4 {
+            let e = rate_matcher.rate_match(&codeword, 3 * (k + 4), rv);
+            let received = rate_matcher.dematch(k, &llrs(&e), rv);
+
+            assert_eq!(
+                TurboCodeword::from_streams(
+                    &llrs(&codeword.d0()),
+                    &llrs(&codeword.d1()),
+                    &llrs(&codeword.d2())
+                ),
+                received
+            );
+        }
+    }
+
+    #[test]
+    fn dematch_punctured() {
+        let k = 40;
+        let rate_matcher = RateMatcher::new(k);
+        let received = rate_matcher.dematch(k, &vec![Llr(4); 2 * k], 0);
+
+        let streams = [received.d0(), received.d1(), received.d2()];
+        let count = streams
+            .iter()
+            .flatten()
+            .filter(|&&x| x != Llr::ZERO)
+            .count();
+        assert_eq!(2 * k, count);
+    }
+
+    #[test]
+    fn dematch_repeated() {
+        let k = 40;
+        let rate_matcher = RateMatcher::new(k);
+        let e = 3 * (k + 4) + 10;
+        let received = rate_matcher.dematch(k, &vec![Llr(4); e], 0);
+
+        let streams = [received.d0(), received.d1(), received.d2()];
+        let flattened: Vec<Llr> = streams.iter().flatten().copied().collect();
+        assert_eq!(10, flattened.iter().filter(|&&x| x == Llr(8)).count());
+        assert_eq!(
+            3 * (k + 4) - 10,
+            flattened.iter().filter(|&&x| x == Llr(4)).count()
+        );
+    }
+
+    #[test]
+    fn fillers_are_not_transmitted() {
+        let k = 40;
+        let rate_matcher = RateMatcher::new(k).with_fillers(5);
+        let received = rate_matcher.dematch(k, &vec![Llr(4); 3 * (k + 4) - 2 * 5], 0);
+
+        assert!(received.systematic[..5].iter().all(|&x| x == Llr::ZERO));
+        assert!(received.first_parity[..5].iter().all(|&x| x == Llr::ZERO));
+        assert!(received.second_parity.iter().all(|&x| x == Llr(4)));
+        assert!(received.systematic[5..].iter().all(|&x| x == Llr(4)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_circular_buffer() {
+        RateMatcher::new(40).with_ncb(0);
+    }
+
+    #[test]
+    fn no_transmittable_positions() {
+        // The first position in the circular buffer is a dummy bit.
+        let rate_matcher = RateMatcher::new(40).with_ncb(1);
+        assert_eq!(None, rate_matcher.position(0));
+        assert_eq!(0, rate_matcher.positions(0, 0).count());
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_transmittable_positions_for_bits() {
+        RateMatcher::new(40).with_ncb(1).positions(1, 0).count();
+    }
+
+    #[test]
+    fn decode_rate_matched() {
+        let k = 40;
+        let input: Vec<bool> = (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let interleaver = LteQpp::get(k).unwrap();
+        let codeword = LteTurboEncoder.encode(&input, interleaver.clone());
+        let rate_matcher = RateMatcher::new(k);
+
+        let e = rate_matcher.rate_match(&codeword, 2 * k, 0);
+        let received = rate_matcher.dematch(k, &llrs(&e), 0);
+
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = turbo.decode_codeword(&received, interleaver);
+
+        let l_app = iterator.nth(3).unwrap();
+        let decoded: Vec<bool> = l_app.iter().map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+    }
+}
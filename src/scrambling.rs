@@ -0,0 +1,243 @@
+//! LTE Scrambling with the Pseudo-Random Gold Sequence, see 36.211 7.2.
+use crate::Llr;
+
+/// The number of initial sequence values that are skipped, `Nc`.
+const NC: usize = 1600;
+
+/// The number of bits the registers can be advanced in a single step.
+const STEP: usize = 16;
+
+/// The length 31 Gold sequence generator.
+/// The registers hold the next 31 values of each m-sequence,
+/// with bit `i` being `x(n + i)` such that multiple values can be generated at a time.
+#[derive(Clone, Debug)]
+pub struct GoldSequence {
+    x1: u32,
+    x2: u32,
+}
+
+impl GoldSequence {
+    /// Create the sequence `c(n)` for the initial value `c_init`.
+    pub fn new(c_init: u32) -> Self {
+        assert!(c_init < 1 << 31);
+        let mut sequence = Self { x1: 1, x2: c_init };
+        for _ in 0..NC / STEP {
+            sequence.advance(STEP);
+        }
+        sequence
+    }
+
+    /// Advance both registers `n <= 16` values.
+    #[inline]
+    fn advance(&mut self, n: usize) {
+        let mask = (1 << n) - 1;
+        // x1(n + 31) = (x1(n + 3) + x1(n)) mod 2
+        let x1 = (self.x1 ^ (self.x1 >> 3)) & mask;
+        // x2(n + 31) = (x2(n + 3) + x2(n + 2) + x2(n + 1) + x2(n)) mod 2
+        let x2 = (self.x2 ^ (self.x2 >> 1) ^ (self.x2 >> 2) ^ (self.x2 >> 3)) & mask;
+        self.x1 = (self.x1 >> n) | (x1 << (31 - n));
+        self.x2 = (self.x2 >> n) | (x2 << (31 - n));
+    }
+
+    /// Get the next `n <= 32` values of the sequence, the first value being in the LSB.
+    #[inline]
+    pub fn next_bits(&mut self, n: usize) -> u32 {
+        assert!(n <= 32);
+        let lo = n.min(STEP);
+        let hi = n - lo;
+
+        let mut word = (self.x1 ^ self.x2) & ((1 << lo) - 1);
+        self.advance(lo);
+        if hi > 0 {
+            word |= ((self.x1 ^ self.x2) & ((1 << hi) - 1)) << STEP;
+            self.advance(hi);
+        }
+        word
+    }
+
+    /// Get the next 32 values of the sequence, the first value being in the LSB.
+    #[inline]
+    pub fn next_word(&mut self) -> u32 {
+        self.next_bits(32)
+    }
+
+    /// Scramble (or descramble) `bits` in place.
+    pub fn scramble_bits(&mut self, bits: &mut [bool]) {
+        for chunk in bits.chunks_mut(32) {
+            let word = self.next_bits(chunk.len());
+            for (i, bit) in chunk.iter_mut().enumerate() {
+                *bit ^= (word >> i) & 1 == 1;
+            }
+        }
+    }
+
+    /// Scramble (or descramble) packed `bytes` in place, where the MSB of each byte is the first bit.
+    pub fn scramble(&mut self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(4) {
+            let word = self.next_bits(8 * chunk.len());
+            for (i, byte) in chunk.iter_mut().enumerate() {
+                *byte ^= ((word >> (8 * i)) as u8).reverse_bits();
+            }
+        }
+    }
+
+    /// Descramble received `llrs` in place by flipping the sign where the sequence is 1.
+    pub fn descramble(&mut self, llrs: &mut [Llr]) {
+        for chunk in llrs.chunks_mut(32) {
+            let word = self.next_bits(chunk.len());
+            for (i, llr) in chunk.iter_mut().enumerate() {
+                if (word >> i) & 1 == 1 {
+                    *llr = llr.saturating_neg();
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for GoldSequence {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_bits(1) == 1)
+    }
+}
+
+/// Initial values `c_init` for the scrambling of the physical channels.
+/// `ns` is the slot number within the radio frame.
+pub mod c_init {
+    /// PDSCH scrambling, see 36.211 6.3.1, where `q` is the codeword number.
+    pub const fn pdsch(rnti: u16, q: u8, ns: u8, cell_id: u16) -> u32 {
+        assert!(q < 2 && ns < 20 && cell_id < 504);
+        ((rnti as u32) << 14) + ((q as u32) << 13) + ((ns as u32 / 2) << 9) + cell_id as u32
+    }
+
+    /// PUSCH scrambling, see 36.211 5.3.1.
+    pub const fn pusch(rnti: u16, ns: u8, cell_id: u16) -> u32 {
+        pdsch(rnti, 0, ns, cell_id)
+    }
+
+    /// PBCH scrambling, see 36.211 6.6.1.
+    pub const fn pbch(cell_id: u16) -> u32 {
+        assert!(cell_id < 504);
+        cell_id as u32
+    }
+
+    /// PCFICH scrambling, see 36.211 6.7.1.
+    pub const fn pcfich(ns: u8, cell_id: u16) -> u32 {
+        assert!(ns < 20 && cell_id < 504);
+        ((((ns as u32 / 2) + 1) * (2 * cell_id as u32 + 1)) << 9) + cell_id as u32
+    }
+
+    /// PDCCH scrambling, see 36.211 6.8.2.
+    pub const fn pdcch(ns: u8, cell_id: u16) -> u32 {
+        assert!(ns < 20 && cell_id < 504);
+        ((ns as u32 / 2) << 9) + cell_id as u32
+    }
+
+    /// PHICH scrambling, see 36.211 6.9.1.
+    pub const fn phich(ns: u8, cell_id: u16) -> u32 {
+        pcfich(ns, cell_id)
+    }
+
+    /// PMCH scrambling, see 36.211 6.5.1.
+    pub const fn pmch(ns: u8, mbsfn_area_id: u8) -> u32 {
+        assert!(ns < 20);
+        ((ns as u32 / 2) << 9) + mbsfn_area_id as u32
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// The sequence computed directly from the definition in 36.211 7.2.
+    fn reference(c_init: u32, len: usize) -> Vec<bool> {
+        let total = NC + len + 31;
+        let mut x1 = vec![0u8; total];
+        let mut x2 = vec![0u8; total];
+        x1[0] = 1;
+        for (i, x) in x2.iter_mut().take(31).enumerate() {
+            *x = ((c_init >> i) & 1) as u8;
+        }
+        for n in 0..total - 31 {
+            x1[n + 31] = (x1[n + 3] + x1[n]) % 2;
+            x2[n + 31] = (x2[n + 3] + x2[n + 2] + x2[n + 1] + x2[n]) % 2;
+        }
+        (0..len)
+            .map(|n| (x1[n + NC] + x2[n + NC]) % 2 == 1)
+            .collect()
+    }
+
+    #[test]
+    fn sequence() {
+        for &c_init in [0, 1, 0x1234, 0x7FFF_FFFF].iter() {
+            let expected = reference(c_init, 200);
+            let actual: Vec<bool> = GoldSequence::new(c_init).take(200).collect();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn next_bits() {
+        let c_init = c_init::pdsch(0x1234, 1, 7, 301);
+        let expected = reference(c_init, 100);
+
+        let mut sequence = GoldSequence::new(c_init);
+        let mut actual = Vec::new();
+        for &n in [32, 5, 17, 1, 32, 13].iter() {
+            let word = sequence.next_bits(n);
+            actual.extend((0..n).map(|i| (word >> i) & 1 == 1));
+        }
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn scramble() {
+        let c_init = c_init::pbch(42);
+        let expected = reference(c_init, 80);
+
+        let mut bits = vec![false; 70];
+        GoldSequence::new(c_init).scramble_bits(&mut bits);
+        assert_eq!(&expected[..70], &bits[..]);
+
+        let mut bytes = [0u8; 10];
+        GoldSequence::new(c_init).scramble(&mut bytes);
+        let unpacked: Vec<bool> = bytes
+            .iter()
+            .flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+            .collect();
+        assert_eq!(expected, unpacked);
+
+        GoldSequence::new(c_init).scramble(&mut bytes);
+        assert_eq!([0u8; 10], bytes);
+    }
+
+    #[test]
+    fn descramble() {
+        let c_init = c_init::pusch(100, 3, 1);
+        let bits: Vec<bool> = (0..50).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+
+        let mut scrambled = bits.clone();
+        GoldSequence::new(c_init).scramble_bits(&mut scrambled);
+
+        let mut llrs: Vec<Llr> = scrambled
+            .iter()
+            .map(|&x| Llr(if x { i8::MAX } else { i8::MIN }))
+            .collect();
+        GoldSequence::new(c_init).descramble(&mut llrs);
+
+        let descrambled: Vec<bool> = llrs.iter().map(|x| x.hard()).collect();
+        assert_eq!(bits, descrambled);
+    }
+
+    #[test]
+    fn c_init() {
+        assert_eq!(
+            (0x1234 << 14) + (1 << 13) + (3 << 9) + 301,
+            c_init::pdsch(0x1234, 1, 7, 301)
+        );
+        assert_eq!(((4 * 21) << 9) + 10, c_init::pcfich(6, 10));
+        assert_eq!((3 << 9) + 10, c_init::pdcch(6, 10));
+    }
+}
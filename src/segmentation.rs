@@ -0,0 +1,281 @@
+//! LTE Code Block Segmentation, see 36.212 5.1.2.
+use crate::{crc::Crc, interleavers::lte::LteQpp, Llr, TurboCodeword};
+use alloc::vec::Vec;
+
+/// The maximum code block size `Z`.
+const MAX_BLOCK_SIZE: usize = 6144;
+
+/// The soft value used for the known zero filler bits.
+pub const FILLER: Llr = Llr(i8::MIN);
+
+/// Code block segmentation of a transport block of `B` bits (including the transport block CRC).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segmentation {
+    /// The number of input bits `B`.
+    b: usize,
+    /// The number of code blocks `C`.
+    c: usize,
+    /// The number of code blocks of size `K-`.
+    c_minus: usize,
+    /// The larger code block size `K+`.
+    k_plus: usize,
+    /// The smaller code block size `K-`.
+    k_minus: usize,
+    /// The number of filler bits `F`.
+    f: usize,
+}
+
+impl Segmentation {
+    /// Compute the segmentation for `b` input bits.
+    pub fn new(b: usize) -> Self {
+        assert!(b > 0);
+
+        let (c, b_prime) = if b <= MAX_BLOCK_SIZE {
+            (1, b)
+        } else {
+            let l = Crc::CRC24B.width();
+            let c = b.div_ceil(MAX_BLOCK_SIZE - l);
+            (c, b + c * l)
+        };
+
+        let k_plus = LteQpp::block_sizes().find(|&k| c * k >= b_prime).unwrap();
+
+        let (c_minus, k_minus) = if c == 1 {
+            (0, 0)
+        } else {
+            let k_minus = LteQpp::block_sizes()
+                .take_while(|&k| k < k_plus)
+                .last()
+                .unwrap();
+            ((c * k_plus - b_prime) / (k_plus - k_minus), k_minus)
+        };
+
+        let f = (c - c_minus) * k_plus + c_minus * k_minus - b_prime;
+
+        Self {
+            b,
+            c,
+            c_minus,
+            k_plus,
+            k_minus,
+            f,
+        }
+    }
+
+    /// The number of code blocks `C`.
+    pub const fn c(&self) -> usize {
+        self.c
+    }
+
+    /// The number of code blocks of size `K-`.
+    pub const fn c_minus(&self) -> usize {
+        self.c_minus
+    }
+
+    /// The number of code blocks of size `K+`.
+    pub const fn c_plus(&self) -> usize {
+        self.c - self.c_minus
+    }
+
+    /// The larger code block size `K+`.
+    pub const fn k_plus(&self) -> usize {
+        self.k_plus
+    }
+
+    /// The smaller code block size `K-`, or 0 if there is only one code block.
+    pub const fn k_minus(&self) -> usize {
+        self.k_minus
+    }
+
+    /// The number of filler bits `F` placed at the start of the first code block.
+    pub const fn fillers(&self) -> usize {
+        self.f
+    }
+
+    /// The size `K_r` of code block `r`.
+    pub const fn k(&self, r: usize) -> usize {
+        if r < self.c_minus {
+            self.k_minus
+        } else {
+            self.k_plus
+        }
+    }
+
+    /// The number of filler bits in code block `r`.
+    pub const fn block_fillers(&self, r: usize) -> usize {
+        if r == 0 {
+            self.f
+        } else {
+            0
+        }
+    }
+
+    /// The CRC attached to each code block, or `None` if there is only one code block.
+    pub const fn block_crc(&self) -> Option<Crc> {
+        if self.c > 1 {
+            Some(Crc::CRC24B)
+        } else {
+            None
+        }
+    }
+
+    /// Segment the `B` input `bits` into code blocks.
+    /// The filler bits are set to zero, and the code block CRC is attached if there is more than one block.
+    pub fn segment(&self, bits: &[bool]) -> Vec<Vec<bool>> {
+        assert_eq!(self.b, bits.len());
+
+        let crc_len = self.block_crc().map_or(0, |crc| crc.width());
+        let mut bits = bits.iter().copied();
+
+        (0..self.c)
+            .map(|r| {
+                let fillers = self.block_fillers(r);
+                let mut block = Vec::with_capacity(self.k(r));
+                block.resize(fillers, false);
+                block.extend(bits.by_ref().take(self.k(r) - fillers - crc_len));
+                if let Some(crc) = self.block_crc() {
+                    crc.attach_bits(&mut block);
+                }
+                debug_assert_eq!(self.k(r), block.len());
+                block
+            })
+            .collect()
+    }
+
+    /// Mark the filler bits in the received `codeword` for code block `r` as known zeros.
+    /// The filler bits are known in both the systematic part and the first parity,
+    /// as the first encoder stays in the all-zero state while encoding them.
+    pub fn mark_fillers(&self, r: usize, codeword: &mut TurboCodeword<Llr>) {
+        let fillers = self.block_fillers(r);
+        codeword.systematic[..fillers].fill(FILLER);
+        codeword.first_parity[..fillers].fill(FILLER);
+    }
+
+    /// Check the decoded soft values of a code block.
+    /// Blocks are always valid if there is only a single code block, as there is no code block CRC.
+    pub fn check_block(&self, llrs: &[Llr]) -> bool {
+        self.block_crc().is_none_or(|crc| crc.check_llrs(llrs))
+    }
+
+    /// Reassemble the `B` bits from the hard decisions of the decoded code `blocks`.
+    /// `None` is returned if any code block fails its CRC check.
+    pub fn reassemble<B: AsRef<[Llr]>>(&self, blocks: &[B]) -> Option<Vec<bool>> {
+        assert_eq!(self.c, blocks.len());
+
+        let crc_len = self.block_crc().map_or(0, |crc| crc.width());
+        let mut bits = Vec::with_capacity(self.b);
+
+        for (r, block) in blocks.iter().enumerate() {
+            let block = block.as_ref();
+            assert_eq!(self.k(r), block.len());
+
+            if !self.check_block(block) {
+                return None;
+            }
+
+            let payload = &block[self.block_fillers(r)..block.len() - crc_len];
+            bits.extend(payload.iter().map(|llr| llr.hard()));
+        }
+
+        debug_assert_eq!(self.b, bits.len());
+        Some(bits)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        encoders::lte::LteTurboEncoder, rate_matching::RateMatcher, trellises::lte::UmtsTrellis,
+        TurboDecoder,
+    };
+    use streaming_iterator::StreamingIterator;
+
+    fn llrs(bits: &[bool]) -> Vec<Llr> {
+        bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+    }
+
+    #[test]
+    fn single_block() {
+        let segmentation = Segmentation::new(6144);
+        assert_eq!(1, segmentation.c());
+        assert_eq!(6144, segmentation.k_plus());
+        assert_eq!(0, segmentation.fillers());
+        assert_eq!(None, segmentation.block_crc());
+
+        let segmentation = Segmentation::new(30);
+        assert_eq!(1, segmentation.c());
+        assert_eq!(40, segmentation.k(0));
+        assert_eq!(10, segmentation.fillers());
+
+        let segmentation = Segmentation::new(1000);
+        assert_eq!(1, segmentation.c());
+        assert_eq!(1008, segmentation.k(0));
+        assert_eq!(8, segmentation.fillers());
+    }
+
+    #[test]
+    fn multiple_blocks() {
+        let segmentation = Segmentation::new(6145);
+        assert_eq!(2, segmentation.c());
+        assert_eq!(3136, segmentation.k_plus());
+        assert_eq!(3072, segmentation.k_minus());
+        assert_eq!(1, segmentation.c_minus());
+        assert_eq!(1, segmentation.c_plus());
+        assert_eq!(15, segmentation.fillers());
+        assert_eq!(3072, segmentation.k(0));
+        assert_eq!(3136, segmentation.k(1));
+
+        let segmentation = Segmentation::new(75376);
+        assert_eq!(13, segmentation.c());
+        assert_eq!(5824, segmentation.k_plus());
+        assert_eq!(5760, segmentation.k_minus());
+        assert_eq!(0, segmentation.c_minus());
+        assert_eq!(24, segmentation.fillers());
+    }
+
+    #[test]
+    fn segment_reassemble() {
+        let b = 6200;
+        let input: Vec<bool> = (0..b).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let segmentation = Segmentation::new(b);
+
+        let blocks = segmentation.segment(&input);
+        assert_eq!(segmentation.c(), blocks.len());
+        assert!(blocks[0][..segmentation.fillers()].iter().all(|&x| !x));
+        assert!(blocks.iter().all(|block| Crc::CRC24B.check_bits(block)));
+
+        let mut received: Vec<Vec<Llr>> = blocks.iter().map(|block| llrs(block)).collect();
+        assert_eq!(Some(input), segmentation.reassemble(&received));
+
+        received[1][10] = Llr(-received[1][10].0);
+        assert_eq!(None, segmentation.reassemble(&received));
+    }
+
+    #[test]
+    fn decode_with_fillers() {
+        let b = 100;
+        let input: Vec<bool> = (0..b).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let segmentation = Segmentation::new(b);
+        let k = segmentation.k(0);
+        assert_eq!(104, k);
+        assert_eq!(4, segmentation.fillers());
+
+        let blocks = segmentation.segment(&input);
+        let interleaver = LteQpp::get(k).unwrap();
+        let codeword = LteTurboEncoder.encode(&blocks[0], interleaver.clone());
+        let rate_matcher = RateMatcher::new(k).with_fillers(segmentation.fillers());
+        let e = rate_matcher.rate_match(&codeword, 2 * k, 0);
+
+        let mut received = rate_matcher.dematch(k, &llrs(&e), 0);
+        segmentation.mark_fillers(0, &mut received);
+        assert_eq!(FILLER, received.systematic[0]);
+        assert_eq!(FILLER, received.first_parity[3]);
+
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = turbo.decode_codeword(&received, interleaver);
+        let l_app = iterator.nth(3).unwrap();
+
+        assert_eq!(Some(input), segmentation.reassemble(&[l_app]));
+    }
+}
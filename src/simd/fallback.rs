@@ -12,6 +12,7 @@ const fn half_add(lhs: i8, rhs: i8) -> i8 {
 }
 
 #[inline(always)]
+#[allow(dead_code)]
 const fn half_sub(lhs: i8, rhs: i8) -> i8 {
     let sum = lhs as i16 - rhs as i16;
     if sum >= 0 {
@@ -3,6 +3,7 @@
 //! https://developer.arm.com/documentation/100166/0001/Programmers-Model/Instruction-set-summary/Table-of-processor-instructions
 //! https://developer.arm.com/documentation/100166/0001/Programmers-Model/Instruction-set-summary/Table-of-processor-DSP-instructions
 use super::*;
+use core::arch::asm;
 
 impl SaturateBits<i32> for i32 {
     #[inline(always)]
@@ -4,12 +4,6 @@ mod m4;
 #[cfg(not(feature = "cortex-m4"))]
 mod fallback;
 
-#[cfg(feature = "cortex-m4")]
-pub use self::m4::*;
-
-#[cfg(not(feature = "cortex-m4"))]
-pub use self::fallback::*;
-
 pub trait SaturateBits<T> {
     // Saturate to given number of bits.
     fn saturate_bits<const BITS: usize>(self) -> T;
@@ -46,6 +40,7 @@ pub trait HalfExt {
     /// Quad 8-bit signed addition with halved results.
     fn half_add_i8(self, rhs: Self) -> Self;
     /// Quad 8-bit signed subtraction with halved results
+    #[allow(dead_code)]
     fn half_sub_i8(self, rhs: Self) -> Self;
 }
 
@@ -53,6 +48,7 @@ pub trait CmpExt {
     /// Quad 8-bit max.
     fn max_i8(self, rhs: Self) -> Self;
     /// Quad 8-bit min.
+    #[allow(dead_code)]
     fn min_i8(self, rhs: Self) -> Self;
 }
 
@@ -0,0 +1,235 @@
+//! Early Stopping Criteria for the Turbo Decoder
+use crate::{crc::Crc, Llr};
+use alloc::vec::Vec;
+
+/// A criterion for stopping the Turbo decoder iterations early.
+pub trait StopCriterion {
+    /// Decide whether to stop after an iteration.
+    /// * `l_app_first` is the a-posteriori llr's from the first decoder, and
+    /// * `l_app` is the de-interleaved a-posteriori llr's from the second decoder.
+    ///
+    /// Both exclude termination and are in natural order.
+    fn should_stop(&mut self, l_app_first: &[Llr], l_app: &[Llr]) -> bool;
+
+    /// Reset any state kept between iterations, before decoding a new block.
+    fn reset(&mut self) {}
+}
+
+impl<F: FnMut(&[Llr], &[Llr]) -> bool> StopCriterion for F {
+    fn should_stop(&mut self, l_app_first: &[Llr], l_app: &[Llr]) -> bool {
+        self(l_app_first, l_app)
+    }
+}
+
+/// Stop when the hard decisions pass the CRC check.
+pub struct CrcStop {
+    crc: Crc,
+}
+
+impl CrcStop {
+    /// Create a criterion for a block that has the parity bits of `crc` attached.
+    pub const fn new(crc: Crc) -> Self {
+        Self { crc }
+    }
+}
+
+impl StopCriterion for CrcStop {
+    fn should_stop(&mut self, _l_app_first: &[Llr], l_app: &[Llr]) -> bool {
+        self.crc.check_llrs(l_app)
+    }
+}
+
+/// Stop when the hard decisions are the same in two consecutive iterations.
+#[derive(Default)]
+pub struct HardDecisionAgreement {
+    /// The hard decisions from the previous iteration.
+    previous: Vec<bool>,
+}
+
+impl HardDecisionAgreement {
+    pub const fn new() -> Self {
+        Self { previous: vec![] }
+    }
+}
+
+impl StopCriterion for HardDecisionAgreement {
+    fn should_stop(&mut self, _l_app_first: &[Llr], l_app: &[Llr]) -> bool {
+        let agree = self.previous.len() == l_app.len()
+            && self
+                .previous
+                .iter()
+                .zip(l_app)
+                .all(|(&previous, llr)| previous == llr.hard());
+
+        self.previous.clear();
+        self.previous.extend(l_app.iter().map(|llr| llr.hard()));
+
+        agree
+    }
+
+    fn reset(&mut self) {
+        self.previous.clear();
+    }
+}
+
+/// Stop when the smallest magnitude of the a-posteriori llr's reaches a threshold.
+pub struct MinAbsLlr {
+    threshold: u8,
+}
+
+impl MinAbsLlr {
+    pub const fn new(threshold: u8) -> Self {
+        Self { threshold }
+    }
+}
+
+impl StopCriterion for MinAbsLlr {
+    fn should_stop(&mut self, _l_app_first: &[Llr], l_app: &[Llr]) -> bool {
+        l_app
+            .iter()
+            .all(|llr| llr.0.unsigned_abs() >= self.threshold)
+    }
+}
+
+/// Stop when the number of hard decisions that differ between the two constituent decoders
+/// is at most `max_changes`.
+pub struct SignChange {
+    max_changes: usize,
+}
+
+impl SignChange {
+    pub const fn new(max_changes: usize) -> Self {
+        Self { max_changes }
+    }
+}
+
+impl StopCriterion for SignChange {
+    fn should_stop(&mut self, l_app_first: &[Llr], l_app: &[Llr]) -> bool {
+        let changes = l_app_first
+            .iter()
+            .zip(l_app)
+            .filter(|(first, second)| first.hard() != second.hard())
+            .count();
+        changes <= self.max_changes
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        encoders::lte::LteTurboEncoder, interleavers::lte::LteQpp, trellises::lte::UmtsTrellis,
+        TurboCodeword, TurboDecoder,
+    };
+    use streaming_iterator::StreamingIterator;
+
+    fn codeword(crc: Crc) -> (Vec<bool>, TurboCodeword<Llr>) {
+        let k = 40;
+        let mut input: Vec<bool> = (0..k - crc.width())
+            .map(|i| (i * 7 + i / 3) % 5 < 2)
+            .collect();
+        crc.attach_bits(&mut input);
+
+        let codeword = LteTurboEncoder.encode(&input, LteQpp::get(k).unwrap());
+        let llr = |&x: &bool| Llr(if x { 4 } else { -4 });
+        let mut received = TurboCodeword {
+            systematic: codeword.systematic.iter().map(llr).collect(),
+            first_systematic_termination: codeword
+                .first_systematic_termination
+                .iter()
+                .map(llr)
+                .collect(),
+            first_parity: codeword.first_parity.iter().map(llr).collect(),
+            second_systematic_termination: codeword
+                .second_systematic_termination
+                .iter()
+                .map(llr)
+                .collect(),
+            second_parity: codeword.second_parity.iter().map(llr).collect(),
+        };
+
+        // Make the first iteration fail.
+        for i in (0..k).step_by(3) {
+            received.systematic[i] = Llr(-received.systematic[i].0);
+            received.first_parity[i + 1] = Llr::ZERO;
+        }
+
+        (input, received)
+    }
+
+    #[test]
+    fn crc_stop() {
+        let (input, received) = codeword(Crc::CRC8);
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = turbo.decode_codeword(&received, LteQpp::get(40).unwrap());
+
+        let iterations = iterator.decode_until(8, &mut CrcStop::new(Crc::CRC8));
+
+        assert!((2..8).contains(&iterations));
+        let decoded: Vec<bool> = iterator.get().unwrap().iter().map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+    }
+
+    #[test]
+    fn hard_decision_agreement() {
+        let (input, received) = codeword(Crc::CRC8);
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = turbo.decode_codeword(&received, LteQpp::get(40).unwrap());
+
+        let mut criterion = HardDecisionAgreement::new();
+        let iterations = iterator.decode_until(8, &mut criterion);
+
+        assert!((2..8).contains(&iterations));
+        let decoded: Vec<bool> = iterator.get().unwrap().iter().map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+    }
+
+    #[test]
+    fn min_abs_llr() {
+        let (_, received) = codeword(Crc::CRC8);
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = turbo.decode_codeword(&received, LteQpp::get(40).unwrap());
+
+        let iterations = iterator.decode_until(8, &mut MinAbsLlr::new(20));
+
+        assert!(iterations < 8);
+        assert!(iterator
+            .get()
+            .unwrap()
+            .iter()
+            .all(|x| x.0.unsigned_abs() >= 20));
+    }
+
+    #[test]
+    fn sign_change() {
+        let (_, received) = codeword(Crc::CRC8);
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = turbo.decode_codeword(&received, LteQpp::get(40).unwrap());
+
+        let iterations = iterator.decode_until(8, &mut SignChange::new(0));
+
+        assert!(iterations < 8);
+        let first: Vec<bool> = iterator
+            .first_decoder_l_app()
+            .iter()
+            .map(|x| x.hard())
+            .collect();
+        let second: Vec<bool> = iterator.get().unwrap().iter().map(|x| x.hard()).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn closure() {
+        let (_, received) = codeword(Crc::CRC8);
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = turbo.decode_codeword(&received, LteQpp::get(40).unwrap());
+
+        let mut calls = 0;
+        let iterations = iterator.decode_until(8, &mut |_: &[Llr], _: &[Llr]| {
+            calls += 1;
+            calls == 3
+        });
+
+        assert_eq!(3, iterations);
+    }
+}
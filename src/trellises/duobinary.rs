@@ -0,0 +1,291 @@
+//! Duo-Binary Circular BCJR Decoder
+use crate::{Llr, SymbolLlr};
+use alloc::vec::Vec;
+
+/// The number of states of the duo-binary constituent code.
+pub const STATES: usize = 8;
+
+/// The period of the state sequence of the constituent code when the input is zero.
+const PERIOD: usize = 7;
+
+/// Advance the 8-state duo-binary recursive systematic code of DVB-RCS and 802.16 from `state` with the couple `(a, b)`,
+/// and return the next state and the parity bits `(y, w)`.
+/// The feedback is `1 + D + D^3`, and the parity outputs are `y: 1 + D^2 + D^3` and `w: 1 + D^3`.
+/// The bit `a` enters only the first register, while `b` enters all three.
+/// The state is `4 S1 + 2 S2 + S3`, where `S1` is the first register.
+pub const fn step(state: u8, a: bool, b: bool) -> (u8, bool, bool) {
+    let s1 = (state >> 2) & 1 == 1;
+    let s2 = (state >> 1) & 1 == 1;
+    let s3 = state & 1 == 1;
+
+    let feedback = a ^ b ^ s1 ^ s3;
+    let y = feedback ^ s2 ^ s3;
+    let w = feedback ^ s3;
+    let next = ((feedback as u8) << 2) | (((s1 ^ b) as u8) << 1) | (s2 ^ b) as u8;
+    (next, y, w)
+}
+
+/// Get the circulation state of a block of `n` couples from the `final_state` reached when it is encoded from the all-zero state,
+/// i.e. the state that the encoder must start in to also end in it, see 802.16 8.4.9.2.3.3.
+/// `n` must not be a multiple of 7.
+pub fn circulation_state(n: usize, final_state: u8) -> u8 {
+    assert!(
+        !n.is_multiple_of(PERIOD),
+        "The block length must not be a multiple of 7."
+    );
+    let zero_input =
+        |state: u8| (0..n % PERIOD).fold(state, |state, _| step(state, false, false).0);
+
+    // Starting in state s ends in the state `zero_input(s) ^ final_state` by linearity.
+    (0..STATES as u8)
+        .find(|&state| zero_input(state) ^ final_state == state)
+        .unwrap()
+}
+
+/// The state metric for impossible states.
+const NEG_INF: i16 = i16::MIN / 2;
+
+/// The next state and the parity bits `2y + w` for each state and symbol `2a + b`.
+const TRANSITIONS: [[(u8, u8); 4]; STATES] = {
+    let mut transitions = [[(0, 0); 4]; STATES];
+    let mut state = 0;
+    while state < STATES {
+        let mut symbol = 0;
+        while symbol < 4 {
+            let (next, y, w) = step(state as u8, symbol & 2 != 0, symbol & 1 != 0);
+            transitions[state][symbol] = (next, 2 * y as u8 + w as u8);
+            symbol += 1;
+        }
+        state += 1;
+    }
+    transitions
+};
+
+/// The state metrics at the ends of a circular trellis, kept between the decodings of a block,
+/// i.e. between the iterations of a Turbo decoder.
+#[derive(Clone, Default)]
+pub struct CircularMetrics {
+    /// The alphas at the end of the block, which are the alphas at the start of the next decoding.
+    alphas: Option<[i16; STATES]>,
+    /// The betas at the start of the block, which are the betas at the end of the next decoding.
+    betas: Option<[i16; STATES]>,
+}
+
+impl CircularMetrics {
+    pub const fn new() -> Self {
+        Self {
+            alphas: None,
+            betas: None,
+        }
+    }
+
+    /// Whether there are any stored metrics.
+    pub fn is_empty(&self) -> bool {
+        self.alphas.is_none() && self.betas.is_none()
+    }
+
+    /// Forget the stored metrics, before decoding a new block.
+    pub fn clear(&mut self) {
+        self.alphas = None;
+        self.betas = None;
+    }
+}
+
+/// Max-log-MAP decoder for the 8-state duo-binary circular (tail-biting) code of DVB-RCS and 802.16,
+/// working on symbol log-likelihoods of the couples.
+/// The unknown circulation state is estimated by the metrics at the ends of the block from the previous decoding,
+/// or by a prologue of `training` steps that wraps around the block if there are none.
+pub struct DuoBinaryTrellis {
+    training: usize,
+}
+
+/// Scale the metrics so that the largest is 0.
+fn normalize(metrics: &mut [i16; STATES]) {
+    let max = metrics.iter().copied().max().unwrap();
+    for metric in metrics.iter_mut() {
+        *metric = metric.saturating_sub(max).max(NEG_INF);
+    }
+}
+
+/// Compute the alphas after a step with the branch metrics `gamma`.
+fn forward(alphas: &[i16; STATES], gamma: &[i16; 16]) -> [i16; STATES] {
+    let mut next = [NEG_INF; STATES];
+    for (transitions, &alpha) in TRANSITIONS.iter().zip(alphas) {
+        for (symbol, &(state, parity)) in transitions.iter().enumerate() {
+            let metric = alpha.saturating_add(gamma[4 * symbol + parity as usize]);
+            let next = &mut next[state as usize];
+            *next = (*next).max(metric);
+        }
+    }
+    normalize(&mut next);
+    next
+}
+
+/// Compute the betas before a step with the branch metrics `gamma`.
+fn backward(betas: &[i16; STATES], gamma: &[i16; 16]) -> [i16; STATES] {
+    let mut previous = [NEG_INF; STATES];
+    for (transitions, previous) in TRANSITIONS.iter().zip(previous.iter_mut()) {
+        for (symbol, &(state, parity)) in transitions.iter().enumerate() {
+            let metric = betas[state as usize].saturating_add(gamma[4 * symbol + parity as usize]);
+            *previous = (*previous).max(metric);
+        }
+    }
+    normalize(&mut previous);
+    previous
+}
+
+impl DuoBinaryTrellis {
+    /// Create a decoder with a prologue of `training` steps.
+    pub const fn new(training: usize) -> Self {
+        Self { training }
+    }
+
+    /// Decode a block of couples, where
+    /// * `systematic` is the llr's of the bits `(A, B)`,
+    /// * `parity` is the llr's of the parity bits `(Y, W)`, and
+    /// * `apriori` is the a-priori symbol log-likelihoods.
+    ///
+    /// All parts must have the same number of elements.
+    /// The metrics at the ends of the block are used and updated in `circular`.
+    /// The returned a-posteriori symbol log-likelihoods have the same length as the input.
+    pub fn decode<
+        S: Iterator<Item = (Llr, Llr)>,
+        P: Iterator<Item = (Llr, Llr)>,
+        La: Iterator<Item = SymbolLlr>,
+    >(
+        &self,
+        systematic: S,
+        parity: P,
+        apriori: La,
+        circular: &mut CircularMetrics,
+    ) -> Vec<SymbolLlr> {
+        // The branch metrics for each symbol `2a + b` and parity `2y + w`.
+        let g_vector: Vec<[i16; 16]> = systematic
+            .zip(parity)
+            .zip(apriori)
+            .map(|(((la, lb), (ly, lw)), apriori)| {
+                let [a, b, y, w] = [la, lb, ly, lw].map(|llr| llr.0 as i16);
+                let symbols = [0, b, a, a + b];
+                let parities = [0, w, y, y + w];
+                let mut gamma = [0; 16];
+                for (index, gamma) in gamma.iter_mut().enumerate() {
+                    let symbol = index / 4;
+                    *gamma = apriori.0[symbol] as i16 + symbols[symbol] + parities[index % 4];
+                }
+                gamma
+            })
+            .collect();
+        let n = g_vector.len();
+        let training = self.training.min(n);
+
+        let mut alphas = circular.alphas.unwrap_or_else(|| {
+            g_vector[n - training..]
+                .iter()
+                .fold([0; STATES], |alphas, gamma| forward(&alphas, gamma))
+        });
+        let mut a_vector = Vec::with_capacity(n);
+        for gamma in &g_vector {
+            a_vector.push(alphas);
+            alphas = forward(&alphas, gamma);
+        }
+        circular.alphas = Some(alphas);
+
+        let mut betas = circular.betas.unwrap_or_else(|| {
+            g_vector[..training]
+                .iter()
+                .rev()
+                .fold([0; STATES], |betas, gamma| backward(&betas, gamma))
+        });
+        let mut l_app = vec![SymbolLlr::ZERO; n];
+        for (t, (alphas, gamma)) in a_vector.iter().zip(&g_vector).enumerate().rev() {
+            let mut max = [i32::MIN; 4];
+            for (transitions, &alpha) in TRANSITIONS.iter().zip(alphas) {
+                for (symbol, &(state, parity)) in transitions.iter().enumerate() {
+                    let metric = alpha as i32
+                        + gamma[4 * symbol + parity as usize] as i32
+                        + betas[state as usize] as i32;
+                    max[symbol] = max[symbol].max(metric);
+                }
+            }
+            l_app[t] = SymbolLlr::from_metrics(max);
+            betas = backward(&betas, gamma);
+        }
+        circular.betas = Some(betas);
+
+        l_app
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Encode `couples` circularly and return the start state and the parity bits `(y, w)`.
+    fn encode(couples: &[(bool, bool)]) -> (u8, Vec<(bool, bool)>) {
+        let final_state = couples.iter().fold(0, |state, &(a, b)| step(state, a, b).0);
+        let start = circulation_state(couples.len(), final_state);
+        let mut state = start;
+        let mut parity = Vec::new();
+        for &(a, b) in couples {
+            let (next, y, w) = step(state, a, b);
+            parity.push((y, w));
+            state = next;
+        }
+        assert_eq!(start, state);
+        (start, parity)
+    }
+
+    fn couples(n: usize) -> Vec<(bool, bool)> {
+        (0..n)
+            .map(|i| ((i * 7 + i / 3) % 5 < 2, (i * 3 + i / 2) % 7 < 3))
+            .collect()
+    }
+
+    fn llr(bit: bool) -> Llr {
+        Llr(if bit { 4 } else { -4 })
+    }
+
+    #[test]
+    fn circulation_states() {
+        // The circulation state for each final state and n mod 7, see 802.16 Table 8-512.
+        let expected = [
+            [0, 6, 4, 2, 7, 1, 3, 5],
+            [0, 3, 7, 4, 5, 6, 2, 1],
+            [0, 5, 3, 6, 2, 7, 1, 4],
+            [0, 4, 1, 5, 6, 2, 7, 3],
+            [0, 2, 5, 7, 1, 3, 4, 6],
+            [0, 7, 6, 1, 3, 4, 5, 2],
+        ];
+        for (n, expected) in (1..7).zip(expected.iter()) {
+            let actual: Vec<u8> = (0..8).map(|state| circulation_state(n, state)).collect();
+            assert_eq!(expected[..], actual[..]);
+        }
+    }
+
+    #[test]
+    fn decode() {
+        let couples = couples(48);
+        let (start, parity) = encode(&couples);
+        assert_ne!(0, start);
+
+        let mut systematic: Vec<(Llr, Llr)> =
+            couples.iter().map(|&(a, b)| (llr(a), llr(b))).collect();
+        let parity: Vec<(Llr, Llr)> = parity.iter().map(|&(y, w)| (llr(y), llr(w))).collect();
+        // Errors at both ends of the circular block.
+        systematic[0].0 = Llr(-systematic[0].0 .0);
+        systematic[47].1 = Llr(-systematic[47].1 .0);
+
+        let mut circular = CircularMetrics::new();
+        for _ in 0..2 {
+            let l_app = DuoBinaryTrellis::new(16).decode(
+                systematic.iter().copied(),
+                parity.iter().copied(),
+                core::iter::repeat(SymbolLlr::ZERO),
+                &mut circular,
+            );
+            let decoded: Vec<(bool, bool)> = l_app.iter().map(|x| x.hard()).collect();
+            assert_eq!(couples, decoded);
+            assert!(!circular.is_empty());
+        }
+    }
+}
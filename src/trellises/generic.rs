@@ -0,0 +1,442 @@
+//! Generic Trellis and Scalar BCJR Decoder
+use crate::{BcjrDecoder, Llr};
+use alloc::vec::Vec;
+
+/// The state metric for impossible states.
+const NEG_INF: i16 = i16::MIN / 2;
+
+/// Trellis of a binary convolutional code with a single input.
+/// The states are the contents of the shift register, with the most recent value in the MSB,
+/// i.e. the same numbering as `RscEncoder::state()` and the states in `UmtsTrellis`.
+/// Generator polynomials are given in the usual octal notation where the MSB is the coefficient of `D^0`,
+/// e.g. `0o13` for `1 + D^2 + D^3` with constraint length 4.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trellis {
+    /// The number of shift register elements.
+    memory: usize,
+    /// The number of outputs for each input.
+    outputs: usize,
+    /// The next state for each state and input.
+    next_state: Vec<[u16; 2]>,
+    /// The output bits for each state and input, where bit `j` is output `j`.
+    output: Vec<[u8; 2]>,
+    /// Whether the input is fed back into the register.
+    recursive: bool,
+}
+
+impl Trellis {
+    /// Create the trellis of a recursive systematic code with the `feedback` polynomial.
+    /// The outputs are the systematic bit followed by a parity bit for each of the `feedforward` polynomials.
+    pub fn recursive(constraint_length: usize, feedback: u32, feedforward: &[u32]) -> Self {
+        assert_eq!(
+            1,
+            feedback >> (constraint_length - 1),
+            "The feedback must include D^0."
+        );
+        Self::build(constraint_length, Some(feedback), feedforward)
+    }
+
+    /// Create the trellis of a feed-forward code, with an output for each of the `generators`.
+    pub fn feedforward(constraint_length: usize, generators: &[u32]) -> Self {
+        Self::build(constraint_length, None, generators)
+    }
+
+    fn build(constraint_length: usize, feedback: Option<u32>, generators: &[u32]) -> Self {
+        assert!((2..=16).contains(&constraint_length));
+        let memory = constraint_length - 1;
+        let outputs = generators.len() + feedback.is_some() as usize;
+        assert!(outputs > 0 && outputs <= 8);
+        assert!(generators
+            .iter()
+            .chain(&feedback)
+            .all(|&g| g >> constraint_length == 0));
+
+        let states = 1usize << memory;
+        let mut next_state = Vec::with_capacity(states);
+        let mut output = Vec::with_capacity(states);
+
+        for state in 0..states {
+            let register = state as u32;
+            let mut next = [0; 2];
+            let mut out = [0; 2];
+
+            for u in 0..2 {
+                let w = match feedback {
+                    // The feedback polynomial includes D^0, so its output is the value shifted in.
+                    Some(feedback) => polynomial_output(feedback, memory, register, u),
+                    None => u,
+                };
+
+                let mut bits = 0;
+                let mut j = 0;
+                if feedback.is_some() {
+                    bits |= u as u8;
+                    j += 1;
+                }
+                for &generator in generators {
+                    let parity = polynomial_output(generator, memory, register, w);
+                    bits |= (parity as u8) << j;
+                    j += 1;
+                }
+
+                next[u as usize] = shift_in(memory, register, w) as u16;
+                out[u as usize] = bits;
+            }
+
+            next_state.push(next);
+            output.push(out);
+        }
+
+        Self {
+            memory,
+            outputs,
+            next_state,
+            output,
+            recursive: feedback.is_some(),
+        }
+    }
+
+    /// The number of states.
+    pub fn states(&self) -> usize {
+        self.next_state.len()
+    }
+
+    /// The number of shift register elements, which is also the number of steps needed to terminate.
+    pub const fn memory(&self) -> usize {
+        self.memory
+    }
+
+    /// The number of outputs for each input.
+    pub const fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// The next state from `state` with the input `u`.
+    pub fn next_state(&self, state: usize, u: bool) -> usize {
+        self.next_state[state][u as usize] as usize
+    }
+
+    /// The output bits from `state` with the input `u`, where bit `j` is output `j`.
+    pub fn output(&self, state: usize, u: bool) -> u8 {
+        self.output[state][u as usize]
+    }
+
+    /// The input that moves `state` towards the all-zero state.
+    pub fn termination_input(&self, state: usize) -> bool {
+        if self.recursive {
+            // The input that makes the value shifted into the register zero.
+            self.next_state(state, true) >> (self.memory - 1) == 0
+        } else {
+            false
+        }
+    }
+
+    /// Encode `input` from the all-zero state, and return the stream for each output.
+    /// If `terminated`, the trellis is driven back to the all-zero state with `memory()` additional steps.
+    pub fn encode(&self, input: &[bool], terminated: bool) -> Vec<Vec<bool>> {
+        let tail_len = if terminated { self.memory } else { 0 };
+        let mut streams = vec![Vec::with_capacity(input.len() + tail_len); self.outputs];
+        let mut state = 0;
+
+        let tail = core::iter::repeat_n(None, tail_len);
+        for u in input.iter().copied().map(Some).chain(tail) {
+            let u = u.unwrap_or_else(|| self.termination_input(state));
+            let bits = self.output(state, u);
+            for (j, stream) in streams.iter_mut().enumerate() {
+                stream.push((bits >> j) & 1 == 1);
+            }
+            state = self.next_state(state, u);
+        }
+
+        debug_assert!(!terminated || state == 0);
+        streams
+    }
+}
+
+/// The state after `w` is shifted into the `register` of `memory` elements.
+pub(super) const fn shift_in(memory: usize, register: u32, w: u32) -> u32 {
+    (w << (memory - 1)) | (register >> 1)
+}
+
+/// The output of the polynomial `poly` when `w` is shifted into the `register` of `memory` elements.
+pub(super) const fn polynomial_output(poly: u32, memory: usize, register: u32, w: u32) -> u32 {
+    // The coefficients of D^1..D^memory are in the same bit order as the register.
+    let delayed = poly & ((1 << memory) - 1);
+    (((poly >> memory) & w) + (delayed & register).count_ones()) & 1
+}
+
+/// Portable max-log-MAP decoder for any trellis with two or more outputs.
+/// The `systematic` part of the decoder input is the first output of the trellis,
+/// and the `parity` part holds the other outputs, in order, for each step.
+/// Punctured outputs are zero.
+pub struct ScalarBcjr {
+    trellis: Trellis,
+}
+
+impl ScalarBcjr {
+    pub fn new(trellis: Trellis) -> Self {
+        assert!(trellis.outputs() >= 2);
+        Self { trellis }
+    }
+
+    pub const fn trellis(&self) -> &Trellis {
+        &self.trellis
+    }
+
+    /// The branch metric for the transition from `state` with input `u`.
+    #[inline]
+    fn gamma(&self, state: usize, u: bool, lu: Llr, lv: &[Llr], la: Llr) -> i16 {
+        let bits = self.trellis.output(state, u);
+        let mut gamma = 0;
+        if u {
+            gamma += la.0 as i16;
+        }
+        if bits & 1 != 0 {
+            gamma += lu.0 as i16;
+        }
+        for (j, lv) in lv.iter().enumerate() {
+            if bits & (2 << j) != 0 {
+                gamma += lv.0 as i16;
+            }
+        }
+        gamma
+    }
+}
+
+/// Scale the metrics so that the largest is 0.
+fn normalize(metrics: &mut [i16]) {
+    let max = metrics.iter().copied().max().unwrap();
+    for metric in metrics.iter_mut() {
+        *metric = metric.saturating_sub(max).max(NEG_INF);
+    }
+}
+
+impl BcjrDecoder for ScalarBcjr {
+    fn parity_outputs(&self) -> usize {
+        self.trellis.outputs() - 1
+    }
+
+    fn decode<Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+        &self,
+        systematic: Lu,
+        parity: Lv,
+        apriori: La,
+        terminated: bool,
+    ) -> Vec<Llr> {
+        let states = self.trellis.states();
+        let input: Vec<(Llr, Llr)> = systematic.zip(apriori).collect();
+        let n = input.len();
+        let parity_outputs = self.parity_outputs();
+        let parity: Vec<Llr> = parity.take(n * parity_outputs).collect();
+        assert_eq!(n * parity_outputs, parity.len());
+        let parity = |t: usize| &parity[t * parity_outputs..(t + 1) * parity_outputs];
+
+        if n == 0 {
+            return Vec::new();
+        }
+
+        // The alphas for each time, starting in the all-zero state.
+        let mut alphas = vec![NEG_INF; states * n];
+        alphas[0] = 0;
+
+        for (t, &(lu, la)) in input.iter().enumerate().take(n.saturating_sub(1)) {
+            let lv = parity(t);
+            let (current, next) = alphas[t * states..(t + 2) * states].split_at_mut(states);
+            for (state, &alpha) in current.iter().enumerate() {
+                for &u in [false, true].iter() {
+                    let metric = alpha.saturating_add(self.gamma(state, u, lu, lv, la));
+                    let next = &mut next[self.trellis.next_state(state, u)];
+                    *next = (*next).max(metric);
+                }
+            }
+            normalize(next);
+        }
+
+        let mut betas = if terminated {
+            let mut betas = vec![NEG_INF; states];
+            betas[0] = 0;
+            betas
+        } else {
+            vec![0; states]
+        };
+        let mut previous = vec![NEG_INF; states];
+        let mut l_app = vec![Llr::ZERO; n];
+
+        for (t, &(lu, la)) in input.iter().enumerate().rev() {
+            let lv = parity(t);
+            let alphas = &alphas[t * states..(t + 1) * states];
+            let mut max = [i32::MIN; 2];
+
+            for (state, &alpha) in alphas.iter().enumerate() {
+                let mut beta_prev = NEG_INF;
+                for &u in [false, true].iter() {
+                    let gamma = self.gamma(state, u, lu, lv, la);
+                    let beta = betas[self.trellis.next_state(state, u)];
+                    let metric = alpha as i32 + gamma as i32 + beta as i32;
+                    max[u as usize] = max[u as usize].max(metric);
+                    beta_prev = beta_prev.max(beta.saturating_add(gamma));
+                }
+                previous[state] = beta_prev;
+            }
+
+            l_app[t] = Llr((max[1] - max[0]).clamp(i8::MIN as i32, i8::MAX as i32) as i8);
+
+            normalize(&mut previous);
+            core::mem::swap(&mut betas, &mut previous);
+        }
+
+        l_app
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        encoders::{lte::LteTurboEncoder, rsc::RscEncoder},
+        interleavers::lte::LteQpp,
+        llr_vec,
+        trellises::lte::UmtsTrellis,
+        TurboCodeword, TurboDecoder,
+    };
+    use streaming_iterator::StreamingIterator;
+
+    fn umts() -> Trellis {
+        Trellis::recursive(4, 0o13, &[0o15])
+    }
+
+    fn llrs(bits: &[bool]) -> Vec<Llr> {
+        bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+    }
+
+    #[test]
+    fn recursive_tables() {
+        let trellis = umts();
+        assert_eq!(8, trellis.states());
+        assert_eq!(3, trellis.memory());
+        assert!(trellis.outputs() >= 2);
+
+        let input: Vec<bool> = (0..40).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let (parity, termination) = RscEncoder::new().encode(input.iter().copied(), true);
+
+        let streams = trellis.encode(&input, true);
+        let systematic: Vec<bool> = input.iter().chain(&termination).copied().collect();
+        assert_eq!(systematic, streams[0]);
+        assert_eq!(parity, streams[1]);
+    }
+
+    #[test]
+    fn same_states_as_rsc_encoder() {
+        let trellis = umts();
+        // Every state is reached with three inputs from the all-zero state.
+        for prefix in 0..8 {
+            let mut encoder = RscEncoder::new();
+            for i in 0..3 {
+                encoder.encode_bit((prefix >> i) & 1 == 1);
+            }
+            let state = encoder.state() as usize;
+            assert_eq!(encoder.termination_bit(), trellis.termination_input(state));
+
+            for &u in [false, true].iter() {
+                let mut next = encoder.clone();
+                let parity = next.encode_bit(u);
+                assert_eq!(next.state() as usize, trellis.next_state(state, u));
+                assert_eq!(u as u8 | (parity as u8) << 1, trellis.output(state, u));
+            }
+        }
+    }
+
+    #[test]
+    fn feedforward_tables() {
+        // The (7, 5) code.
+        let trellis = Trellis::feedforward(3, &[0o7, 0o5]);
+        assert_eq!(4, trellis.states());
+
+        let streams = trellis.encode(&[true, false, true, true], true);
+        assert_eq!(vec![true, true, false, false, false, true], streams[0]);
+        assert_eq!(vec![true, false, false, true, true, true], streams[1]);
+    }
+
+    #[test]
+    fn decode_same_as_umts() {
+        let systematic = llr_vec![4, 4, -4, 4, 4, -4, -4, 4, -4, -4, -4];
+        let parity = llr_vec![4, -4, -4, 4, 4, -4, 4, 4, -4, -4, -4];
+        let apriori = llr_vec![0, 2, -2, 0, 0, -6, 0, 0, 0, 0, 0];
+
+        let bcjr = ScalarBcjr::new(umts());
+        let expected = UmtsTrellis.decode(
+            systematic.iter().copied(),
+            parity.iter().copied(),
+            apriori.iter().copied(),
+            true,
+        );
+        let actual = bcjr.decode(
+            systematic.iter().copied(),
+            parity.iter().copied(),
+            apriori.iter().copied(),
+            true,
+        );
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn decode_empty() {
+        let bcjr = ScalarBcjr::new(umts());
+        for &terminated in [true, false].iter() {
+            let l_app = bcjr.decode(
+                core::iter::empty(),
+                core::iter::empty(),
+                core::iter::empty(),
+                terminated,
+            );
+            assert!(l_app.is_empty());
+        }
+    }
+
+    #[test]
+    fn decode_feedforward() {
+        let trellis = Trellis::feedforward(3, &[0o7, 0o5]);
+        let input: Vec<bool> = (0..30).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let streams = trellis.encode(&input, true);
+
+        let mut first = llrs(&streams[0]);
+        let mut second = llrs(&streams[1]);
+        // Erase some of the received values.
+        for i in (0..first.len()).step_by(4) {
+            first[i] = Llr::ZERO;
+            second[i + 2] = Llr::ZERO;
+        }
+
+        let l_app = ScalarBcjr::new(trellis).decode(
+            first.into_iter(),
+            second.into_iter(),
+            core::iter::repeat(Llr::ZERO),
+            true,
+        );
+        let decoded: Vec<bool> = l_app.iter().take(input.len()).map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+    }
+
+    #[test]
+    fn turbo_decode() {
+        let k = 40;
+        let input: Vec<bool> = (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let codeword = LteTurboEncoder.encode(&input, LteQpp::get(k).unwrap());
+        let mut received = TurboCodeword {
+            systematic: llrs(&codeword.systematic),
+            first_systematic_termination: llrs(&codeword.first_systematic_termination),
+            first_parity: llrs(&codeword.first_parity),
+            second_systematic_termination: llrs(&codeword.second_systematic_termination),
+            second_parity: llrs(&codeword.second_parity),
+        };
+        for i in (0..k).step_by(3) {
+            received.systematic[i] = Llr(-received.systematic[i].0);
+        }
+
+        let mut turbo = TurboDecoder::new(ScalarBcjr::new(umts()));
+        let mut iterator = turbo.decode_codeword(&received, LteQpp::get(k).unwrap());
+        let l_app = iterator.nth(2).unwrap();
+        let decoded: Vec<bool> = l_app.iter().map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+    }
+}
@@ -1,11 +1,48 @@
 //! UMTS BCJR Decoder
 #![allow(unused_attributes)]
 #![cfg_attr(rustfmt, rustfmt_skip)]
-use crate::{dword::DWord, simd::*, BcjrDecoder, Llr};
+use super::packed::Routing;
+use crate::{dword::DWord, simd::*, BcjrDecoder, BoundaryMetrics, Llr};
 use alloc::{collections::VecDeque, vec::Vec};
+use core::ops::Range;
 
+/// Max-log-MAP decoder for the 8-state constituent code of the 3GPP Turbo code.
 pub struct UmtsTrellis;
 
+/// Log-MAP decoder for the 8-state constituent code of the 3GPP Turbo code.
+/// It uses the Jacobian logarithm with a small correction table instead of the max-log-MAP approximation,
+/// which gives a better coding gain at a higher computational cost.
+/// The correction assumes that the llr's have 2 fractional bits, i.e. that `Llr(4)` is 1 nat.
+/// Unlike the max-log-MAP decoders, the result is therefore not independent of the scale of the input.
+pub struct UmtsLogMapTrellis;
+
+/// Sliding window max-log-MAP decoder for the 8-state constituent code of the 3GPP Turbo code.
+/// The block is processed in windows of `window` steps, where the backward recursion for each window
+/// is started `training` steps beyond its end from equiprobable states.
+/// The memory used is proportional to the window and training lengths instead of the block length.
+pub struct UmtsSlidingWindowTrellis {
+    window: usize,
+    training: usize,
+}
+
+/// Radix-4 max-log-MAP decoder for the 8-state constituent code of the 3GPP Turbo code.
+/// The recursions advance two trellis steps at a time with a maximum over the four two-step paths into each state,
+/// using precomputed two-step branch metrics, which halves the number of recursion steps, normalizations
+/// and stored alphas compared to `UmtsTrellis`. The llr's of both steps are computed from the same paths.
+/// The branch metrics are centered around 0, so that the sum over two steps has the range of a single step of `UmtsTrellis`.
+pub struct UmtsRadix4Trellis;
+
+/// Parallel max-log-MAP decoder for the 8-state constituent code of the 3GPP Turbo code.
+/// The block is split into `sub_blocks` sub-blocks of about equal length with independent recursions,
+/// such that they can be processed concurrently.
+/// The recursions are started at the sub-block boundaries from the metrics of the previous decoding of the block
+/// (next iteration initialization) when decoded with `decode_with_boundaries`, and from equiprobable states otherwise.
+/// The `BcjrDecoder` implementation processes the sub-blocks one after another on the calling core,
+/// while `decode_sub_block` decodes a single sub-block, such that the caller can spread them over several cores.
+pub struct UmtsParallelTrellis {
+    sub_blocks: usize,
+}
+
 struct StateBytes {
     /// The values for states 7-4.
     s74: DWord,
@@ -21,212 +58,986 @@ impl BcjrDecoder for UmtsTrellis {
         apriori: La,
         terminated: bool,
     ) -> Vec<Llr> {
-        let capacity = systematic.size_hint().1
-            .or(parity.size_hint().1)
-            .or(apriori.size_hint().1)
-            .unwrap_or_else(|| systematic.size_hint().0);
-        let mut g_vector = Vec::with_capacity(capacity);
-
-        for ((lu, lv), la) in systematic.zip(parity).zip(apriori) {
-            // Inner product of possible transmitted symbols and their received value.
-            // G from state emitting u=0/v=0: 0*La + 0*LU - 0*LV
-            // G from state emitting u=0/v=1: 0*La + 0*LU + 1*LV
-            // G from state emitting u=1/v=0: 1*La + 1*LU - 0*LV
-            // G from state emitting u=1/v=1: 1*La + 1*LU + 1*LV
-
-            let g0p1 = lv.0 as i32;
-            let g1p0 = la.0 as i32 + lu.0 as i32;
-            let g1p1 = g0p1 + g1p0;
-
-            let g0p0: i8 = 0;
-            let g0p1: i8 = g0p1.saturate_into();
-            let g1p0: i8 = g1p0.saturate_into();
-            let g1p1: i8 = g1p1.saturate_into();
-
-            let bytes = [
-                g0p0 as u8,
-                g0p1 as u8,
-                g1p0 as u8,
-                g1p1 as u8,
-            ];
-
-            g_vector.push(DWord::new_u32(u32::from_le_bytes(bytes)));
-        }
+        decode::<MaxLog, _, _, _>(systematic, parity, apriori, terminated)
+    }
+}
 
-        assert!(
-            g_vector.len() >= 6,
-            "The input is not long enough to open and close the trellis."
-        );
+impl BcjrDecoder for UmtsLogMapTrellis {
+    fn decode<Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+        &self,
+        systematic: Lu,
+        parity: Lv,
+        apriori: La,
+        terminated: bool,
+    ) -> Vec<Llr> {
+        decode::<LogMap, _, _, _>(systematic, parity, apriori, terminated)
+    }
+}
 
-        let mut a_vector = Vec::with_capacity(g_vector.len());
-        let mut l_app = VecDeque::with_capacity(g_vector.len());
+impl UmtsSlidingWindowTrellis {
+    /// Create a decoder with the `window` and `training` lengths.
+    /// The output is identical to `UmtsTrellis` when the sum of the two is at least the block length.
+    pub const fn new(window: usize, training: usize) -> Self {
+        assert!(window > 0);
+        Self { window, training }
+    }
+}
 
-        let (forward, tail) = g_vector.split_at(g_vector.len() - 3);
-        let mut forward = forward.iter().copied();
+impl BcjrDecoder for UmtsSlidingWindowTrellis {
+    fn decode<Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+        &self,
+        systematic: Lu,
+        parity: Lv,
+        apriori: La,
+        terminated: bool,
+    ) -> Vec<Llr> {
+        decode_windowed::<MaxLog, _, _, _>(systematic, parity, apriori, terminated, self.window, self.training)
+    }
+}
 
-        // Only s0 is valid.
-        let mut a74 = DWord::new_u32(0x80808080);
-        let mut a30 = DWord::new_u32(0x80808000);
-
-        // Only s4 and s0 are valid.
-        let g = forward.next().unwrap();
-        let a74us = compute_a74(a74, a30, g) & 0x000000FF;
-        let a30us = compute_a30(a74, a30, g) & 0x000000FF;
-        let coefficients = scale_coeff2((a74us << 8) | a30us);
-        a74 = a74us.saturating_sub_i8(coefficients & 0x000000FF) | 0x80808000;
-        a30 = a30us.saturating_sub_i8(coefficients & 0x000000FF) | 0x80808000;
-        a_vector.push(StateBytes { s74: a74, s30: a30 });
+impl BcjrDecoder for UmtsRadix4Trellis {
+    fn decode<Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+        &self,
+        systematic: Lu,
+        parity: Lv,
+        apriori: La,
+        terminated: bool,
+    ) -> Vec<Llr> {
+        decode_radix4::<MaxLog, _, _, _>(systematic, parity, apriori, terminated)
+    }
+}
 
-        // Only s6, s4, s2 and s0 are valid.
-        let g = forward.next().unwrap();
-        let a74us = compute_a74(a74, a30, g) & 0x00FF00FF;
-        let a30us = compute_a30(a74, a30, g) & 0x00FF00FF;
-        let coefficients = scale_coeff4((a74us << 8) | a30us);
-        a74 = a74us.saturating_sub_i8(coefficients & 0x00FF00FF) | 0x80008000;
-        a30 = a30us.saturating_sub_i8(coefficients & 0x00FF00FF) | 0x80008000;
-        a_vector.push(StateBytes { s74: a74, s30: a30 });
+impl UmtsParallelTrellis {
+    /// Create a decoder that splits each block into `sub_blocks` sub-blocks.
+    pub const fn new(sub_blocks: usize) -> Self {
+        assert!(sub_blocks > 0);
+        Self { sub_blocks }
+    }
+
+    /// The number of sub-blocks used for a block of `n` steps, including termination.
+    pub fn sub_blocks(&self, n: usize) -> usize {
+        self.sub_blocks.min(n)
+    }
+
+    /// The steps of `sub_block` in a block of `n` steps, including termination.
+    pub fn sub_block_range(&self, n: usize, sub_block: usize) -> Range<usize> {
+        let sub_blocks = self.sub_blocks(n);
+        sub_block * n / sub_blocks..(sub_block + 1) * n / sub_blocks
+    }
+
+    /// Decode a single sub-block of a block, independently of the other sub-blocks,
+    /// such that the sub-blocks of a block can be decoded on different cores.
+    /// The `systematic`, `parity` and `apriori` parts are for the entire block, including termination,
+    /// and the a-posteriori llr's of the sub-block are written to `l_app`, which has the length of the sub-block.
+    /// The recursions are started from the `previous` metrics if they were produced for the same split,
+    /// and the new metrics are returned as the alphas at the end of the sub-block and the betas at its start,
+    /// which are used for the next decoding with `BoundaryMetrics::store()`.
+    /// Decoding all the sub-blocks this way gives the same result as `decode_with_boundaries`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn decode_sub_block(
+        &self,
+        sub_block: usize,
+        systematic: &[Llr],
+        parity: &[Llr],
+        apriori: &[Llr],
+        terminated: bool,
+        previous: &BoundaryMetrics,
+        l_app: &mut [Llr],
+    ) -> ([i16; 8], [i16; 8]) {
+        let n = systematic.len();
+        assert!(parity.len() == n && apriori.len() == n);
+        assert!(n >= 6, "The input is not long enough to open and close the trellis.");
+        let range = self.sub_block_range(n, sub_block);
+        assert_eq!(range.len(), l_app.len());
+
+        let g_vector: Vec<DWord> = range
+            .clone()
+            .map(|t| branch_metrics(systematic[t], parity[t], apriori[t]))
+            .collect();
+        decode_sub_block::<MaxLog>(&g_vector, sub_block, range.start, n, terminated, self.sub_blocks(n), previous, l_app)
+    }
+}
+
+impl BcjrDecoder for UmtsParallelTrellis {
+    fn decode<Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+        &self,
+        systematic: Lu,
+        parity: Lv,
+        apriori: La,
+        terminated: bool,
+    ) -> Vec<Llr> {
+        decode_parallel::<MaxLog, _, _, _>(systematic, parity, apriori, terminated, self.sub_blocks, &mut BoundaryMetrics::new())
+    }
+
+    fn decode_with_boundaries<Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+        &self,
+        systematic: Lu,
+        parity: Lv,
+        apriori: La,
+        terminated: bool,
+        boundaries: &mut BoundaryMetrics,
+    ) -> Vec<Llr> {
+        decode_parallel::<MaxLog, _, _, _>(systematic, parity, apriori, terminated, self.sub_blocks, boundaries)
+    }
+}
+
+/// The operation used to merge the metrics of two paths.
+trait MaxOp {
+    /// Quad 8-bit merge of path metrics.
+    fn max(lhs: DWord, rhs: DWord) -> DWord;
+}
+
+/// The max-log-MAP approximation `max*(a, b) ~ max(a, b)`.
+struct MaxLog;
+
+/// The Jacobian logarithm `max*(a, b) = max(a, b) + ln(1 + exp(-|a - b|))`.
+struct LogMap;
 
-        for g in forward {
-            // All states are valid.
-            let a74us = compute_a74(a74, a30, g);
-            let a30us = compute_a30(a74, a30, g);
-            let coefficients = scale_coeff8(a74us, a30us);
-            a74 = a74us.saturating_sub_i8(coefficients);
-            a30 = a30us.saturating_sub_i8(coefficients);
-            a_vector.push(StateBytes { s74: a74, s30: a30 });
+impl MaxOp for MaxLog {
+    #[inline(always)]
+    fn max(lhs: DWord, rhs: DWord) -> DWord {
+        lhs.max_i8(rhs)
+    }
+}
+
+impl LogMap {
+    /// The correction term `ln(1 + exp(-|a - b|))` in units of 1/4 nat, for `|a - b|` in units of 1/4 nat,
+    /// is the number of thresholds that are larger than `|a - b|`, i.e. it is 3, 2, 2, 2, 1, 1, 1, 1, 1
+    /// for the differences 0 to 8 and 0 for larger differences.
+    const THRESHOLDS: [u32; 3] = [1, 4, 9];
+}
+
+impl MaxOp for LogMap {
+    #[inline(always)]
+    fn max(lhs: DWord, rhs: DWord) -> DWord {
+        let max = lhs.max_i8(rhs);
+        let diff = lhs.saturating_sub_i8(rhs).max_i8(rhs.saturating_sub_i8(lhs));
+
+        // Each lane of the difference is non-negative, so it is below a threshold if the sign bit is set after subtraction.
+        let mut correction = 0;
+        for threshold in Self::THRESHOLDS.iter() {
+            correction += (diff.saturating_sub_i8(DWord::new_u32(threshold * 0x01010101)).u32() & 0x80808080) >> 7;
         }
 
-        // Only s3, s2, s1 and s0 are valid.
-        let g = tail[0];
-        let a30us = compute_a30(a74, a30, g);
-        let coefficients = scale_coeff4(a30us);
-        a74 = DWord::new_u32(0x80808080);
+        // Merging two invalid states stays at -inf.
+        let invalid = (max.saturating_sub_i8(DWord::new_u32(0x81818181)).u32() & 0x80808080) >> 7;
+        max.saturating_add_i8(DWord::new_u32(correction & !(invalid * 0xFF)))
+    }
+}
+
+fn decode<M: MaxOp, Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+    systematic: Lu,
+    parity: Lv,
+    apriori: La,
+    terminated: bool,
+) -> Vec<Llr> {
+    let capacity = systematic.size_hint().1
+        .or(parity.size_hint().1)
+        .or(apriori.size_hint().1)
+        .unwrap_or_else(|| systematic.size_hint().0);
+    let mut g_vector = Vec::with_capacity(capacity);
+
+    for ((lu, lv), la) in systematic.zip(parity).zip(apriori) {
+        g_vector.push(branch_metrics(lu, lv, la));
+    }
+
+    assert!(
+        g_vector.len() >= 6,
+        "The input is not long enough to open and close the trellis."
+    );
+
+    let mut a_vector = Vec::with_capacity(g_vector.len());
+    let mut l_app = VecDeque::with_capacity(g_vector.len());
+
+    let (forward, tail) = g_vector.split_at(g_vector.len() - 3);
+    let mut forward = forward.iter().copied();
+
+    // Only s0 is valid.
+    let mut a74 = DWord::new_u32(0x80808080);
+    let mut a30 = DWord::new_u32(0x80808000);
+
+    // Only s4 and s0 are valid.
+    let g = forward.next().unwrap();
+    let a74us = compute_a74::<M>(a74, a30, g) & 0x000000FF;
+    let a30us = compute_a30::<M>(a74, a30, g) & 0x000000FF;
+    let coefficients = scale_coeff2((a74us << 8) | a30us);
+    a74 = a74us.saturating_sub_i8(coefficients & 0x000000FF) | 0x80808000;
+    a30 = a30us.saturating_sub_i8(coefficients & 0x000000FF) | 0x80808000;
+    a_vector.push(StateBytes { s74: a74, s30: a30 });
+
+    // Only s6, s4, s2 and s0 are valid.
+    let g = forward.next().unwrap();
+    let a74us = compute_a74::<M>(a74, a30, g) & 0x00FF00FF;
+    let a30us = compute_a30::<M>(a74, a30, g) & 0x00FF00FF;
+    let coefficients = scale_coeff4((a74us << 8) | a30us);
+    a74 = a74us.saturating_sub_i8(coefficients & 0x00FF00FF) | 0x80008000;
+    a30 = a30us.saturating_sub_i8(coefficients & 0x00FF00FF) | 0x80008000;
+    a_vector.push(StateBytes { s74: a74, s30: a30 });
+
+    for g in forward {
+        // All states are valid.
+        let a74us = compute_a74::<M>(a74, a30, g);
+        let a30us = compute_a30::<M>(a74, a30, g);
+        let coefficients = scale_coeff8(a74us, a30us);
+        a74 = a74us.saturating_sub_i8(coefficients);
         a30 = a30us.saturating_sub_i8(coefficients);
         a_vector.push(StateBytes { s74: a74, s30: a30 });
+    }
 
-        // Only s1 and s0 are valid.
-        let g = tail[1];
-        let a30us = compute_a30(a74, a30, g) & 0x0000FFFF;
-        let coefficients = scale_coeff2(a30us);
-        a74 = DWord::new_u32(0x80808080);
-        a30 = a30us.saturating_sub_i8(coefficients & 0x0000FFFF) | 0x80800000;
-        a_vector.push(StateBytes { s74: a74, s30: a30 });
+    // Only s3, s2, s1 and s0 are valid.
+    let g = tail[0];
+    let a30us = compute_a30::<M>(a74, a30, g);
+    let coefficients = scale_coeff4(a30us);
+    a74 = DWord::new_u32(0x80808080);
+    a30 = a30us.saturating_sub_i8(coefficients);
+    a_vector.push(StateBytes { s74: a74, s30: a30 });
+
+    // Only s1 and s0 are valid.
+    let g = tail[1];
+    let a30us = compute_a30::<M>(a74, a30, g) & 0x0000FFFF;
+    let coefficients = scale_coeff2(a30us);
+    a74 = DWord::new_u32(0x80808080);
+    a30 = a30us.saturating_sub_i8(coefficients & 0x0000FFFF) | 0x80800000;
+    a_vector.push(StateBytes { s74: a74, s30: a30 });
+
+    // We do not use the last value of g in the forward path.
+    // Proceed with backward path.
+
+    let forward = a_vector.iter().zip(&g_vector[1..]).map(|x| (x.0, *x.1));
+    let mut head = forward.clone().take(2).rev();
+    let mut backward = forward.skip(2).rev();
+
+    let (mut b74, mut b30) = if terminated {
+        // Only s0 is valid.
+        let b74 = DWord::new_u32(0x80808080);
+        let mut b30 = DWord::new_u32(0x80808000);
+
+        {
+            let (a, g) = backward.next().unwrap();
+
+            // Emit llr.
+            let max1 = compute_max1::<M>(a.s74, a.s30, g, b74, b30, 0x00000000, 0x0000FFFF);
+            let max0 = compute_max0::<M>(a.s74, a.s30, g, b74, b30, 0x00000000, 0x0000FFFF);
+            l_app.push_front(max1.saturating_sub(max0));
 
-        // We do not use the last value of g in the forward path.
-        // Proceed with backward path.
+            // Only s1 and s0 are valid.
+            let b30us = compute_b30::<M>(b74, b30, g) & 0x0000FFFF;
+            let coefficients = scale_coeff2(b30us);
+            // B74 remains -inf.
+            b30 = b30us.saturating_sub_i8(coefficients & 0x0000FFFF) | 0x80800000;
+        }
 
-        let forward = a_vector.iter().zip(&g_vector[1..]).map(|x| (x.0, *x.1));
-        let mut head = forward.clone().take(2).rev();
-        let mut backward = forward.skip(2).rev();
+        {
+            let (a, g) = backward.next().unwrap();
 
-        let (mut b74, mut b30) = if terminated {
-            // Only s0 is valid.
-            let b74 = DWord::new_u32(0x80808080);
-            let mut b30 = DWord::new_u32(0x80808000);
+            // Emit llr.
+            let max1 = compute_max1::<M>(a.s74, a.s30, g, b74, b30, 0x00000000, 0xFFFFFFFF);
+            let max0 = compute_max0::<M>(a.s74, a.s30, g, b74, b30, 0x00000000, 0xFFFFFFFF);
+            l_app.push_front(max1.saturating_sub(max0));
 
-            {
-                let (a, g) = backward.next().unwrap();
+            // Only s3, s2, s1 and s0 are valid.
+            let b30us = compute_b30::<M>(b74, b30, g);
+            let coefficients = scale_coeff4(b30us);
+            // B74 remains -inf.
+            b30 = b30us.saturating_sub_i8(coefficients);
+        }
 
-                // Emit llr.
-                let max1 = compute_max1(a.s74, a.s30, g, b74, b30, 0x00000000, 0x0000FFFF);
-                let max0 = compute_max0(a.s74, a.s30, g, b74, b30, 0x00000000, 0x0000FFFF);
-                l_app.push_front(max1.saturating_sub(max0));
+        (b74, b30)
+    } else {
+        let b74 = DWord::new_u32(0x00000000);
+        let b30 = DWord::new_u32(0x00000000);
+
+        (b74, b30)
+    };
+
+    for (a, g) in backward {
+        // Emit llr.
+        let max1 = compute_max1::<M>(a.s74, a.s30, g, b74, b30, 0xFFFFFFFF, 0xFFFFFFFF);
+        let max0 = compute_max0::<M>(a.s74, a.s30, g, b74, b30, 0xFFFFFFFF, 0xFFFFFFFF);
+        l_app.push_front(max1.saturating_sub(max0));
+
+        // All states are valid.
+        let b74us = compute_b74::<M>(b74, b30, g);
+        let b30us = compute_b30::<M>(b74, b30, g);
+        let coefficients = scale_coeff8(b74us, b30us);
+        b74 = b74us.saturating_sub_i8(coefficients);
+        b30 = b30us.saturating_sub_i8(coefficients);
+    }
 
-                // Only s1 and s0 are valid.
-                let b30us = compute_b30(b74, b30, g) & 0x0000FFFF;
-                let coefficients = scale_coeff2(b30us);
-                // B74 remains -inf.
-                b30 = b30us.saturating_sub_i8(coefficients & 0x0000FFFF) | 0x80800000;
-            }
+    {
+        let (a, g) = head.next().unwrap();
 
-            {
-                let (a, g) = backward.next().unwrap();
+        // Emit llr.
+        let max1 = compute_max1::<M>(a.s74, a.s30, g, b74, b30, 0x00FF00FF, 0x00FF00FF);
+        let max0 = compute_max0::<M>(a.s74, a.s30, g, b74, b30, 0x00FF00FF, 0x00FF00FF);
+        l_app.push_front(max1.saturating_sub(max0));
 
-                // Emit llr.
-                let max1 = compute_max1(a.s74, a.s30, g, b74, b30, 0x00000000, 0xFFFFFFFF);
-                let max0 = compute_max0(a.s74, a.s30, g, b74, b30, 0x00000000, 0xFFFFFFFF);
-                l_app.push_front(max1.saturating_sub(max0));
+        // Only s6, s4, s2 and s0 are valid.
+        let b74us = compute_b74::<M>(b74, b30, g) & 0x00FF00FF;
+        let b30us = compute_b30::<M>(b74, b30, g) & 0x00FF00FF;
+        let coefficients = scale_coeff4((b74us << 8) | b30us);
+        b74 = b74us.saturating_sub_i8(coefficients & 0x00FF00FF) | 0x80008000;
+        b30 = b30us.saturating_sub_i8(coefficients & 0x00FF00FF) | 0x80008000;
+    }
 
-                // Only s3, s2, s1 and s0 are valid.
-                let b30us = compute_b30(b74, b30, g);
-                let coefficients = scale_coeff4(b30us);
-                // B74 remains -inf.
-                b30 = b30us.saturating_sub_i8(coefficients);
+    {
+        let (a, g) = head.next().unwrap();
+
+        // Emit llr.
+        let max1 = compute_max1::<M>(a.s74, a.s30, g, b74, b30, 0x000000FF, 0x000000FF);
+        let max0 = compute_max0::<M>(a.s74, a.s30, g, b74, b30, 0x000000FF, 0x000000FF);
+        l_app.push_front(max1.saturating_sub(max0));
+
+        /* Only s4 and s0 are valid. */
+        let b74us = compute_b74::<M>(b74, b30, g) & 0x000000FF;
+        let b30us = compute_b30::<M>(b74, b30, g) & 0x000000FF;
+        let coefficients = scale_coeff2((b74us << 8) | b30us);
+        b74 = b74us.saturating_sub_i8(coefficients & 0x000000FF) | 0x80808000;
+        b30 = b30us.saturating_sub_i8(coefficients & 0x000000FF) | 0x80808000;
+    }
+
+    {
+        let a74 = DWord::new_u32(0x80808080);
+        let a30 = DWord::new_u32(0x80808000);
+        let g = g_vector[0];
+
+        // Emit llr.
+        let max1 = compute_max1::<M>(a74, a30, g, b74, b30, 0x00000000, 0x000000FF);
+        let max0 = compute_max0::<M>(a74, a30, g, b74, b30, 0x00000000, 0x000000FF);
+        l_app.push_front(max1.saturating_sub(max0));
+    }
+
+    debug_assert!(head.next().is_none());
+
+    l_app.make_contiguous().to_vec()
+}
+
+/// Compute the branch metrics for a single step.
+#[inline]
+pub(super) fn branch_metrics(lu: Llr, lv: Llr, la: Llr) -> DWord {
+    // Inner product of possible transmitted symbols and their received value.
+    // G from state emitting u=0/v=0: 0*La + 0*LU - 0*LV
+    // G from state emitting u=0/v=1: 0*La + 0*LU + 1*LV
+    // G from state emitting u=1/v=0: 1*La + 1*LU - 0*LV
+    // G from state emitting u=1/v=1: 1*La + 1*LU + 1*LV
+
+    let g0p1 = lv.0 as i32;
+    let g1p0 = la.0 as i32 + lu.0 as i32;
+    let g1p1 = g0p1 + g1p0;
+
+    let g0p0: i8 = 0;
+    let g0p1: i8 = g0p1.saturate_into();
+    let g1p0: i8 = g1p0.saturate_into();
+    let g1p1: i8 = g1p1.saturate_into();
+
+    let bytes = [
+        g0p0 as u8,
+        g0p1 as u8,
+        g1p0 as u8,
+        g1p1 as u8,
+    ];
+
+    DWord::new_u32(u32::from_le_bytes(bytes))
+}
+
+/// Compute the branch metrics for a single step, offset so that they are symmetric around 0.
+/// The offset is the same for all the branches of a step, so it does not change the llr's,
+/// but it halves the largest magnitude, which leaves room for adding the branch metrics of two steps.
+#[inline]
+fn centered_branch_metrics(lu: Llr, lv: Llr, la: Llr) -> DWord {
+    let g0p1 = lv.0 as i32;
+    let g1p0 = la.0 as i32 + lu.0 as i32;
+    let g1p1 = g0p1 + g1p0;
+    let offset = g1p1 >> 1;
+
+    let bytes = [
+        SaturateInto::<i8>::saturate_into(-offset) as u8,
+        SaturateInto::<i8>::saturate_into(g0p1 - offset) as u8,
+        SaturateInto::<i8>::saturate_into(g1p0 - offset) as u8,
+        SaturateInto::<i8>::saturate_into(g1p1 - offset) as u8,
+    ];
+
+    DWord::new_u32(u32::from_le_bytes(bytes))
+}
+
+fn decode_windowed<M: MaxOp, Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+    systematic: Lu,
+    parity: Lv,
+    apriori: La,
+    terminated: bool,
+    window: usize,
+    training: usize,
+) -> Vec<Llr> {
+    let capacity = systematic.size_hint().1
+        .or(parity.size_hint().1)
+        .or(apriori.size_hint().1)
+        .unwrap_or_else(|| systematic.size_hint().0);
+    let mut l_app = Vec::with_capacity(capacity);
+
+    let mut metrics = systematic.zip(parity).zip(apriori).map(|((lu, lv), la)| branch_metrics(lu, lv, la));
+
+    // The branch metrics for the current window and its training, starting at time `offset`.
+    // The three extra values tell whether the window reaches the termination.
+    let mut g_buffer = VecDeque::with_capacity(window + training + 3);
+    let mut offset = 0;
+    // The block length, once all metrics are read.
+    let mut n = usize::MAX;
+
+    let mut a_window = Vec::with_capacity(window);
+
+    // Only s0 is valid.
+    let mut a74 = DWord::new_u32(0x80808080);
+    let mut a30 = DWord::new_u32(0x80808000);
+
+    let mut start = 0;
+    while start < n {
+        while n == usize::MAX && offset + g_buffer.len() < start + window + training + 3 {
+            match metrics.next() {
+                Some(g) => g_buffer.push_back(g),
+                None => n = offset + g_buffer.len(),
             }
+        }
 
+        assert!(
+            n >= 6,
+            "The input is not long enough to open and close the trellis."
+        );
+
+        let end = (start + window).min(n);
+        let g = |t: usize| g_buffer[t - offset];
+
+        // Forward path over the window.
+        a_window.clear();
+        for t in start..end {
+            a_window.push(StateBytes { s74: a74, s30: a30 });
+            if t + 1 < n {
+                let (a74next, a30next) = forward_step::<M>(t, n, a74, a30, g(t));
+                a74 = a74next;
+                a30 = a30next;
+            }
+        }
+
+        // Train the backward path from beyond the window unless it is close to the end of the block.
+        let (mut b74, mut b30) = if end + training + 3 < n {
+            let mut b74 = DWord::new_u32(0x00000000);
+            let mut b30 = DWord::new_u32(0x00000000);
+            for t in (end..end + training).rev() {
+                let (b74prev, b30prev) = backward_step::<M>(t, n, terminated, b74, b30, g(t));
+                b74 = b74prev;
+                b30 = b30prev;
+            }
             (b74, b30)
         } else {
-            let b74 = DWord::new_u32(0x00000000);
-            let b30 = DWord::new_u32(0x00000000);
-
+            let mut b74 = if terminated { DWord::new_u32(0x80808080) } else { DWord::new_u32(0x00000000) };
+            let mut b30 = if terminated { DWord::new_u32(0x80808000) } else { DWord::new_u32(0x00000000) };
+            for t in (end..n).rev() {
+                let (b74prev, b30prev) = backward_step::<M>(t, n, terminated, b74, b30, g(t));
+                b74 = b74prev;
+                b30 = b30prev;
+            }
             (b74, b30)
         };
 
-        for (a, g) in backward {
+        // Backward path over the window.
+        l_app.resize(end, Llr::ZERO);
+        for t in (start..end).rev() {
+            let a = &a_window[t - start];
+            let (a74_valid, a30_valid) = valid_states(t, n, terminated);
+
             // Emit llr.
-            let max1 = compute_max1(a.s74, a.s30, g, b74, b30, 0xFFFFFFFF, 0xFFFFFFFF);
-            let max0 = compute_max0(a.s74, a.s30, g, b74, b30, 0xFFFFFFFF, 0xFFFFFFFF);
-            l_app.push_front(max1.saturating_sub(max0));
+            let max1 = compute_max1::<M>(a.s74, a.s30, g(t), b74, b30, a74_valid, a30_valid);
+            let max0 = compute_max0::<M>(a.s74, a.s30, g(t), b74, b30, a74_valid, a30_valid);
+            l_app[t] = max1.saturating_sub(max0);
+
+            if t > 0 {
+                let (b74prev, b30prev) = backward_step::<M>(t, n, terminated, b74, b30, g(t));
+                b74 = b74prev;
+                b30 = b30prev;
+            }
+        }
 
-            // All states are valid.
-            let b74us = compute_b74(b74, b30, g);
-            let b30us = compute_b30(b74, b30, g);
-            let coefficients = scale_coeff8(b74us, b30us);
-            b74 = b74us.saturating_sub_i8(coefficients);
-            b30 = b30us.saturating_sub_i8(coefficients);
+        g_buffer.drain(..end - offset);
+        offset = end;
+        start = end;
+    }
+
+    l_app
+}
+
+/// The lane routing of the radix-4 recursions, where each step covers two trellis steps.
+/// The two-step branch metrics are the 16 sums of a branch metric lane of the first step and one of the second,
+/// where lane `b` of word `a` is the sum of lane `a` of the first and lane `b` of the second, see `radix4_gammas()`.
+/// The state metrics and the state masks are the words of states 3-0 and 7-4.
+struct Radix4Routing {
+    /// The routing of the alphas along the four two-step paths into the states of each word.
+    alpha: [[Routing; 4]; 2],
+    /// The routing of the middle states of the four two-step paths into the states of each word.
+    alpha_middle: [[Routing; 4]; 2],
+    /// The routing of the two-step branch metrics of the four paths into the states of each word.
+    alpha_gamma: [[Routing; 4]; 2],
+    /// The routing of the betas along the two-step path with the inputs `2 u1 + u2` out of the states of each word.
+    beta: [[Routing; 4]; 2],
+    /// The routing of the middle states of the path with the inputs `2 u1 + u2` out of the states of each word.
+    beta_middle: [[Routing; 4]; 2],
+    /// The routing of the two-step branch metrics of the path with the inputs `2 u1 + u2` out of the states of each word.
+    beta_gamma: [[Routing; 4]; 2],
+}
+
+impl Radix4Routing {
+    /// Generate the routing from the state transitions of the encoder, see `transition()`.
+    const fn new() -> Self {
+        // The sources for each word, path and lane.
+        let mut alpha = [[[(0, 0); 4]; 4]; 2];
+        let mut alpha_middle = [[[(0, 0); 4]; 4]; 2];
+        let mut alpha_gamma = [[[(0, 0); 4]; 4]; 2];
+        let mut beta = [[[(0, 0); 4]; 4]; 2];
+        let mut beta_middle = [[[(0, 0); 4]; 4]; 2];
+        let mut beta_gamma = [[[(0, 0); 4]; 4]; 2];
+        let mut paths = [0; 8];
+
+        let mut state = 0;
+        while state < 8 {
+            let mut inputs = 0;
+            while inputs < 4 {
+                let (middle, first) = transition(state, inputs >> 1);
+                let (next, second) = transition(middle, inputs & 1);
+
+                let path = paths[next];
+                alpha[next / 4][path][next % 4] = (state / 4, state % 4);
+                alpha_middle[next / 4][path][next % 4] = (middle / 4, middle % 4);
+                alpha_gamma[next / 4][path][next % 4] = (first, second);
+                paths[next] += 1;
+
+                beta[state / 4][inputs][state % 4] = (next / 4, next % 4);
+                beta_middle[state / 4][inputs][state % 4] = (middle / 4, middle % 4);
+                beta_gamma[state / 4][inputs][state % 4] = (first, second);
+                inputs += 1;
+            }
+            state += 1;
         }
 
-        {
-            let (a, g) = head.next().unwrap();
+        let mut routing = Self {
+            alpha: [[Routing::EMPTY; 4]; 2],
+            alpha_middle: [[Routing::EMPTY; 4]; 2],
+            alpha_gamma: [[Routing::EMPTY; 4]; 2],
+            beta: [[Routing::EMPTY; 4]; 2],
+            beta_middle: [[Routing::EMPTY; 4]; 2],
+            beta_gamma: [[Routing::EMPTY; 4]; 2],
+        };
 
-            // Emit llr.
-            let max1 = compute_max1(a.s74, a.s30, g, b74, b30, 0x00FF00FF, 0x00FF00FF);
-            let max0 = compute_max0(a.s74, a.s30, g, b74, b30, 0x00FF00FF, 0x00FF00FF);
-            l_app.push_front(max1.saturating_sub(max0));
+        let mut word = 0;
+        while word < 2 {
+            let mut path = 0;
+            while path < 4 {
+                routing.alpha[word][path] = Routing::new(alpha[word][path]);
+                routing.alpha_middle[word][path] = Routing::new(alpha_middle[word][path]);
+                routing.alpha_gamma[word][path] = Routing::new(alpha_gamma[word][path]);
+                routing.beta[word][path] = Routing::new(beta[word][path]);
+                routing.beta_middle[word][path] = Routing::new(beta_middle[word][path]);
+                routing.beta_gamma[word][path] = Routing::new(beta_gamma[word][path]);
+                path += 1;
+            }
+            word += 1;
+        }
+
+        routing
+    }
+}
+
+const RADIX4: Radix4Routing = Radix4Routing::new();
+
+/// Get the next state and the branch metric lane `2 u + v` from a `state` with the input `u`,
+/// with the state numbering of `RscEncoder`.
+const fn transition(state: usize, u: usize) -> (usize, usize) {
+    let feedback = ((state >> 1) ^ state) & 1;
+    let a = u ^ feedback;
+    let parity = a ^ (((state >> 2) ^ state) & 1);
+    ((a << 2) | (state >> 1), 2 * u + parity)
+}
 
-            // Only s6, s4, s2 and s0 are valid.
-            let b74us = compute_b74(b74, b30, g) & 0x00FF00FF;
-            let b30us = compute_b30(b74, b30, g) & 0x00FF00FF;
-            let coefficients = scale_coeff4((b74us << 8) | b30us);
-            b74 = b74us.saturating_sub_i8(coefficients & 0x00FF00FF) | 0x80008000;
-            b30 = b30us.saturating_sub_i8(coefficients & 0x00FF00FF) | 0x80008000;
+/// Get the state masks as words of states 3-0 and 7-4.
+#[inline]
+fn mask_words(valid: (u32, u32)) -> [DWord; 2] {
+    [DWord::new_u32(valid.1), DWord::new_u32(valid.0)]
+}
+
+/// Set the lanes that are not in the mask to -inf.
+#[inline]
+fn mask_lanes(value: DWord, mask: DWord) -> DWord {
+    (value & mask) | (0x80808080 & !mask.u32())
+}
+
+/// Compute the 16 two-step branch metrics from the branch metrics `g0` and `g1` of two consecutive steps.
+#[inline]
+fn radix4_gammas(g0: DWord, g1: DWord) -> [DWord; 4] {
+    let mut gammas = [g1; 4];
+    for (lane, gamma) in gammas.iter_mut().enumerate() {
+        let splat = DWord::new_u32(((g0.u32() >> (8 * lane)) & 0xFF) * 0x01010101);
+        *gamma = splat.saturating_add_i8(g1);
+    }
+    gammas
+}
+
+/// Compute the alphas two steps after time `t` in a block of length `n`, with a single normalization.
+/// The paths through states that are not valid are excluded.
+#[inline]
+fn radix4_forward<M: MaxOp>(t: usize, n: usize, a74: DWord, a30: DWord, gammas: &[DWord; 4]) -> (DWord, DWord) {
+    let alphas = [a30, a74];
+    let valid = mask_words(valid_states(t, n, true));
+    let middle_valid = mask_words(valid_states(t + 1, n, true));
+
+    let mut next = [DWord::new_u32(0x80808080); 2];
+    for (word, next) in next.iter_mut().enumerate() {
+        for path in 0..4 {
+            let alpha = RADIX4.alpha[word][path].apply(&alphas);
+            let gamma = RADIX4.alpha_gamma[word][path].apply(gammas);
+            let mask = RADIX4.alpha[word][path].apply(&valid) & RADIX4.alpha_middle[word][path].apply(&middle_valid);
+            *next = M::max(*next, mask_lanes(alpha.saturating_add_i8(gamma), mask));
         }
+    }
+    normalize(valid_states(t + 2, n, true), next[1], next[0])
+}
 
-        {
-            let (a, g) = head.next().unwrap();
+/// Compute the sums of the betas and the two-step branch metrics for the paths with the inputs `2 u1 + u2`
+/// out of the states of each word, excluding the paths through states that are not in the masks.
+#[inline]
+fn radix4_paths(b74: DWord, b30: DWord, gammas: &[DWord; 4], valid: &[DWord; 2], middle_valid: &[DWord; 2]) -> [[DWord; 2]; 4] {
+    let betas = [b30, b74];
+    let mut paths = [[DWord::new_u32(0x80808080); 2]; 4];
+    for (inputs, path) in paths.iter_mut().enumerate() {
+        for (word, sum) in path.iter_mut().enumerate() {
+            let beta = RADIX4.beta[word][inputs].apply(&betas);
+            let gamma = RADIX4.beta_gamma[word][inputs].apply(gammas);
+            let mask = RADIX4.beta[word][inputs].apply(valid) & RADIX4.beta_middle[word][inputs].apply(middle_valid);
+            *sum = mask_lanes(beta.saturating_add_i8(gamma), mask);
+        }
+    }
+    paths
+}
 
-            // Emit llr.
-            let max1 = compute_max1(a.s74, a.s30, g, b74, b30, 0x000000FF, 0x000000FF);
-            let max0 = compute_max0(a.s74, a.s30, g, b74, b30, 0x000000FF, 0x000000FF);
-            l_app.push_front(max1.saturating_sub(max0));
+/// Get the largest metric of the paths with the given inputs through the `alphas`, as an llr.
+#[inline]
+fn radix4_max<M: MaxOp>(a74: DWord, a30: DWord, paths: &[[DWord; 2]; 4], inputs: [usize; 2]) -> Llr {
+    let mut max = DWord::new_u32(0x80808080);
+    for &inputs in inputs.iter() {
+        max = M::max(max, a30.saturating_add_i8(paths[inputs][0]));
+        max = M::max(max, a74.saturating_add_i8(paths[inputs][1]));
+    }
+    let max = M::max(max, max >> 16);
+    let max = M::max(max, max >> 8);
+    Llr((max.u32() & 0xFF) as i8)
+}
 
-            /* Only s4 and s0 are valid. */
-            let b74us = compute_b74(b74, b30, g) & 0x000000FF;
-            let b30us = compute_b30(b74, b30, g) & 0x000000FF;
-            let coefficients = scale_coeff2((b74us << 8) | b30us);
-            b74 = b74us.saturating_sub_i8(coefficients & 0x000000FF) | 0x80808000;
-            b30 = b30us.saturating_sub_i8(coefficients & 0x000000FF) | 0x80808000;
+fn decode_radix4<M: MaxOp, Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+    systematic: Lu,
+    parity: Lv,
+    apriori: La,
+    terminated: bool,
+) -> Vec<Llr> {
+    let capacity = systematic.size_hint().1
+        .or(parity.size_hint().1)
+        .or(apriori.size_hint().1)
+        .unwrap_or_else(|| systematic.size_hint().0);
+    let mut g_vector = Vec::with_capacity(capacity);
+
+    for ((lu, lv), la) in systematic.zip(parity).zip(apriori) {
+        g_vector.push(centered_branch_metrics(lu, lv, la));
+    }
+
+    assert!(
+        g_vector.len() >= 6,
+        "The input is not long enough to open and close the trellis."
+    );
+
+    // Each recursion step takes the maximum over the four two-step paths into or out of each state,
+    // using the precomputed sums of the branch metrics of both steps, and normalizes once.
+    // The alphas are only stored for the even times.
+    let n = g_vector.len();
+    let mut a_vector = Vec::with_capacity(n.div_ceil(2));
+
+    // Only s0 is valid.
+    let mut a74 = DWord::new_u32(0x80808080);
+    let mut a30 = DWord::new_u32(0x80808000);
+
+    for t in (0..n).step_by(2) {
+        a_vector.push(StateBytes { s74: a74, s30: a30 });
+
+        // We do not use the alphas after the last value of g.
+        if t + 2 < n {
+            let gammas = radix4_gammas(g_vector[t], g_vector[t + 1]);
+            (a74, a30) = radix4_forward::<M>(t, n, a74, a30, &gammas);
         }
+    }
 
-        {
-            let a74 = DWord::new_u32(0x80808080);
-            let a30 = DWord::new_u32(0x80808000);
-            let g = g_vector[0];
+    let mut l_app = vec![Llr::ZERO; n];
 
-            // Emit llr.
-            let max1 = compute_max1(a74, a30, g, b74, b30, 0x00000000, 0x000000FF);
-            let max0 = compute_max0(a74, a30, g, b74, b30, 0x00000000, 0x000000FF);
-            l_app.push_front(max1.saturating_sub(max0));
+    let (mut b74, mut b30, mut b_valid) = if terminated {
+        // Only s0 is valid.
+        (DWord::new_u32(0x80808080), DWord::new_u32(0x80808000), (0x00000000, 0x000000FF))
+    } else {
+        (DWord::new_u32(0x00000000), DWord::new_u32(0x00000000), (0xFFFFFFFF, 0xFFFFFFFF))
+    };
+
+    // The last step of an odd block length is a single step.
+    let mut pairs = n;
+    if n % 2 == 1 {
+        let t = n - 1;
+        let a = &a_vector[t / 2];
+        let (a74_valid, a30_valid) = valid_states(t, n, terminated);
+        let g = g_vector[t];
+
+        // Emit llr.
+        let max1 = compute_max1::<M>(a.s74, a.s30, g, b74, b30, a74_valid, a30_valid);
+        let max0 = compute_max0::<M>(a.s74, a.s30, g, b74, b30, a74_valid, a30_valid);
+        l_app[t] = max1.saturating_sub(max0);
+
+        (b74, b30) = backward_step::<M>(t, n, terminated, b74, b30, g);
+        b_valid = valid_states(t, n, terminated);
+        pairs -= 1;
+    }
+
+    for t in (0..pairs).step_by(2).rev() {
+        let gammas = radix4_gammas(g_vector[t], g_vector[t + 1]);
+        let b_mask = mask_words(b_valid);
+
+        // The llr of the first step uses the betas of the middle states and the llr of the second step the alphas,
+        // which differ in the valid states at the end of a block that is not terminated.
+        let backward_valid = valid_states(t + 1, n, terminated);
+        let forward_valid = valid_states(t + 1, n, true);
+        let backward = radix4_paths(b74, b30, &gammas, &b_mask, &mask_words(backward_valid));
+        let forward = if forward_valid == backward_valid {
+            backward
+        } else {
+            radix4_paths(b74, b30, &gammas, &b_mask, &mask_words(forward_valid))
+        };
+
+        // Emit the llr's of both steps.
+        let a = &a_vector[t / 2];
+        let (a74, a30) = mask(valid_states(t, n, true), a.s74, a.s30);
+        let first1 = radix4_max::<M>(a74, a30, &backward, [2, 3]);
+        let first0 = radix4_max::<M>(a74, a30, &backward, [0, 1]);
+        let second1 = radix4_max::<M>(a74, a30, &forward, [1, 3]);
+        let second0 = radix4_max::<M>(a74, a30, &forward, [0, 2]);
+        l_app[t] = first1.saturating_sub(first0);
+        l_app[t + 1] = second1.saturating_sub(second0);
+
+        // Compute the betas of the previous step.
+        let mut b = [DWord::new_u32(0x80808080); 2];
+        for path in backward.iter() {
+            b[0] = M::max(b[0], path[0]);
+            b[1] = M::max(b[1], path[1]);
         }
+        b_valid = valid_states(t, n, terminated);
+        (b74, b30) = normalize(b_valid, b[1], b[0]);
+    }
+
+    l_app
+}
+
+/// Decode the sub-blocks one after another, see `UmtsParallelTrellis::decode_sub_block()`.
+fn decode_parallel<M: MaxOp, Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+    systematic: Lu,
+    parity: Lv,
+    apriori: La,
+    terminated: bool,
+    sub_blocks: usize,
+    boundaries: &mut BoundaryMetrics,
+) -> Vec<Llr> {
+    let capacity = systematic.size_hint().1
+        .or(parity.size_hint().1)
+        .or(apriori.size_hint().1)
+        .unwrap_or_else(|| systematic.size_hint().0);
+    let mut g_vector = Vec::with_capacity(capacity);
+
+    for ((lu, lv), la) in systematic.zip(parity).zip(apriori) {
+        g_vector.push(branch_metrics(lu, lv, la));
+    }
+
+    assert!(
+        g_vector.len() >= 6,
+        "The input is not long enough to open and close the trellis."
+    );
+
+    let n = g_vector.len();
+    let sub_blocks = sub_blocks.min(n);
+    let bound = |p: usize| p * n / sub_blocks;
+
+    // The metrics produced at the boundaries are only used by the next decoding,
+    // so that the sub-blocks do not depend on each other.
+    let previous = core::mem::take(boundaries);
+    boundaries.reset(8, sub_blocks);
+
+    let mut l_app = vec![Llr::ZERO; n];
+    for p in 0..sub_blocks {
+        let (start, end) = (bound(p), bound(p + 1));
+        let (alphas, betas) = decode_sub_block::<M>(&g_vector[start..end], p, start, n, terminated, sub_blocks, &previous, &mut l_app[start..end]);
+        boundaries.store(p, &alphas, &betas);
+    }
+
+    l_app
+}
+
+/// Get the metrics of states 7-4 and 3-0 in state order.
+fn to_metrics(s74: DWord, s30: DWord) -> [i16; 8] {
+    let mut metrics = [0; 8];
+    for lane in 0..4 {
+        metrics[lane] = (s30.u32() >> (8 * lane)) as u8 as i8 as i16;
+        metrics[lane + 4] = (s74.u32() >> (8 * lane)) as u8 as i8 as i16;
+    }
+    metrics
+}
+
+/// Get the metrics of states 7-4 and 3-0 from the metrics in state order.
+fn from_metrics(metrics: &[i16]) -> (DWord, DWord) {
+    let mut s74 = 0;
+    let mut s30 = 0;
+    for lane in 0..4 {
+        let byte = |metric: i16| metric.clamp(i8::MIN as i16, i8::MAX as i16) as i8 as u8 as u32;
+        s30 |= byte(metrics[lane]) << (8 * lane);
+        s74 |= byte(metrics[lane + 4]) << (8 * lane);
+    }
+    (DWord::new_u32(s74), DWord::new_u32(s30))
+}
+
+/// Decode the sub-block `p` with the branch metrics `g_vector` that starts at time `start` in a block of length `n`,
+/// and return the alphas at its end and the betas at its start.
+#[allow(clippy::too_many_arguments)]
+fn decode_sub_block<M: MaxOp>(
+    g_vector: &[DWord],
+    p: usize,
+    start: usize,
+    n: usize,
+    terminated: bool,
+    sub_blocks: usize,
+    previous: &BoundaryMetrics,
+    l_app: &mut [Llr],
+) -> ([i16; 8], [i16; 8]) {
+    let end = start + g_vector.len();
+
+    // The previous metrics are only usable if the block was split the same way.
+    let equiprobable = (DWord::new_u32(0x00000000), DWord::new_u32(0x00000000));
+    let usable = previous.states() == 8 && previous.sub_blocks() == sub_blocks;
+
+    let (mut a74, mut a30) = if start == 0 {
+        // Only s0 is valid.
+        (DWord::new_u32(0x80808080), DWord::new_u32(0x80808000))
+    } else if usable {
+        from_metrics(previous.alphas(p))
+    } else {
+        equiprobable
+    };
+
+    // Forward path over the sub-block.
+    let mut a_sub_block = Vec::with_capacity(g_vector.len());
+    for (t, &g) in (start..end).zip(g_vector) {
+        a_sub_block.push(StateBytes { s74: a74, s30: a30 });
+        if t + 1 < n {
+            let (a74next, a30next) = forward_step::<M>(t, n, a74, a30, g);
+            a74 = a74next;
+            a30 = a30next;
+        }
+    }
+    let alphas = to_metrics(a74, a30);
+
+    let (mut b74, mut b30) = if end < n {
+        if usable {
+            from_metrics(previous.betas(p))
+        } else {
+            equiprobable
+        }
+    } else if terminated {
+        // Only s0 is valid.
+        (DWord::new_u32(0x80808080), DWord::new_u32(0x80808000))
+    } else {
+        equiprobable
+    };
+
+    // Backward path over the sub-block.
+    for t in (start..end).rev() {
+        let a = &a_sub_block[t - start];
+        let (a74_valid, a30_valid) = valid_states(t, n, terminated);
+        let g = g_vector[t - start];
+
+        // Emit llr.
+        let max1 = compute_max1::<M>(a.s74, a.s30, g, b74, b30, a74_valid, a30_valid);
+        let max0 = compute_max0::<M>(a.s74, a.s30, g, b74, b30, a74_valid, a30_valid);
+        l_app[t - start] = max1.saturating_sub(max0);
+
+        if t > 0 {
+            let (b74prev, b30prev) = backward_step::<M>(t, n, terminated, b74, b30, g);
+            b74 = b74prev;
+            b30 = b30prev;
+        }
+    }
+
+    (alphas, to_metrics(b74, b30))
+}
+
+/// Compute the alphas after the step at time `t` in a block of length `n`.
+#[inline]
+fn forward_step<M: MaxOp>(t: usize, n: usize, a74: DWord, a30: DWord, g: DWord) -> (DWord, DWord) {
+    let a74next = compute_a74::<M>(a74, a30, g);
+    let a30next = compute_a30::<M>(a74, a30, g);
+    normalize(valid_states(t + 1, n, true), a74next, a30next)
+}
+
+/// Compute the betas before the step at time `t` in a block of length `n`.
+#[inline]
+fn backward_step<M: MaxOp>(t: usize, n: usize, terminated: bool, b74: DWord, b30: DWord, g: DWord) -> (DWord, DWord) {
+    let b74prev = compute_b74::<M>(b74, b30, g);
+    let b30prev = compute_b30::<M>(b74, b30, g);
+    normalize(valid_states(t, n, terminated), b74prev, b30prev)
+}
+
+/// Set the states that are not `valid` to -inf.
+#[inline]
+fn mask(valid: (u32, u32), s74: DWord, s30: DWord) -> (DWord, DWord) {
+    (
+        (s74 & valid.0) | (0x80808080 & !valid.0),
+        (s30 & valid.1) | (0x80808080 & !valid.1),
+    )
+}
 
-        debug_assert!(head.next().is_none());
+/// Scale the `valid` states so that they sum to 0, and set the others to -inf.
+#[inline]
+fn normalize(valid: (u32, u32), s74: DWord, s30: DWord) -> (DWord, DWord) {
+    let s74 = s74 & valid.0;
+    let s30 = s30 & valid.1;
+    let coefficients = match valid {
+        (0x000000FF, 0x000000FF) => scale_coeff2((s74 << 8) | s30),
+        (0x00FF00FF, 0x00FF00FF) => scale_coeff4((s74 << 8) | s30),
+        (0x00000000, 0x0000FFFF) => scale_coeff2(s30),
+        (0x00000000, 0xFFFFFFFF) => scale_coeff4(s30),
+        _ => scale_coeff8(s74, s30),
+    };
+    mask(
+        valid,
+        s74.saturating_sub_i8(coefficients & valid.0),
+        s30.saturating_sub_i8(coefficients & valid.1),
+    )
+}
 
-        l_app.make_contiguous().to_vec()
+/// Get the masks of the valid alphas and betas at time `t` in a block of length `n`.
+#[inline]
+fn valid_states(t: usize, n: usize, terminated: bool) -> (u32, u32) {
+    if t == 0 {
+        (0x00000000, 0x000000FF)
+    } else if t == 1 {
+        (0x000000FF, 0x000000FF)
+    } else if t == 2 {
+        (0x00FF00FF, 0x00FF00FF)
+    } else if terminated && t + 1 == n {
+        (0x00000000, 0x0000FFFF)
+    } else if terminated && t + 2 == n {
+        (0x00000000, 0xFFFFFFFF)
+    } else {
+        (0xFFFFFFFF, 0xFFFFFFFF)
     }
 }
 
 #[inline]
-fn compute_a74(a74_prev: DWord, a30_prev: DWord, g: DWord) -> DWord {
+#[allow(unused_parens, clippy::double_parens)] // The parentheses align the lanes.
+fn compute_a74<M: MaxOp>(a74_prev: DWord, a30_prev: DWord, g: DWord) -> DWord {
     // Case when u=0 is transmitted.
     let a74 =                           // pr     cr u/v
         ((a74_prev & 0x00FFFF00) <<  8) |   // s6 --> s7 0/0
@@ -257,11 +1068,12 @@ fn compute_a74(a74_prev: DWord, a30_prev: DWord, g: DWord) -> DWord {
 
     let one74 = a74.saturating_add_i8(g74);
 
-    zero74.max_i8(one74)
+    M::max(zero74, one74)
 }
 
 #[inline]
-fn compute_a30(a74_prev: DWord, a30_prev: DWord, g: DWord) -> DWord {
+#[allow(unused_parens, clippy::double_parens)] // The parentheses align the lanes.
+fn compute_a30<M: MaxOp>(a74_prev: DWord, a30_prev: DWord, g: DWord) -> DWord {
     // Case when u=0 is transmitted.
     let a30 =                           // pr     cr u/v
         ((a74_prev & 0xFF000000)      ) |   // s7 --> s3 0/0
@@ -292,11 +1104,12 @@ fn compute_a30(a74_prev: DWord, a30_prev: DWord, g: DWord) -> DWord {
 
     let one30 = a30.saturating_add_i8(g30);
 
-    zero30.max_i8(one30)
+    M::max(zero30, one30)
 }
 
 #[inline]
-fn compute_b74(b74_next: DWord, b30_next: DWord, g: DWord) -> DWord {
+#[allow(unused_parens, clippy::double_parens)] // The parentheses align the lanes.
+fn compute_b74<M: MaxOp>(b74_next: DWord, b30_next: DWord, g: DWord) -> DWord {
     // Case when u=0 is transmitted.
     let b74 =                           // cr     nx u/v
         ((b30_next & 0xFF000000)      ) |   // s7 <-- s3 0/0
@@ -327,11 +1140,12 @@ fn compute_b74(b74_next: DWord, b30_next: DWord, g: DWord) -> DWord {
 
     let one74 = b74.saturating_add_i8(g74);
 
-    zero74.max_i8(one74)
+    M::max(zero74, one74)
 }
 
 #[inline]
-fn compute_b30(b74_next: DWord, b30_next: DWord, g: DWord) -> DWord {
+#[allow(unused_parens, clippy::double_parens)] // The parentheses align the lanes.
+fn compute_b30<M: MaxOp>(b74_next: DWord, b30_next: DWord, g: DWord) -> DWord {
     // Case when u=0 is transmitted.
     let b30 =                           // cr     nx u/v
         ((b30_next & 0x0000FF00) << 16) |   // s3 <-- s1 0/1
@@ -362,11 +1176,12 @@ fn compute_b30(b74_next: DWord, b30_next: DWord, g: DWord) -> DWord {
 
     let one30 = b30.saturating_add_i8(g30);
 
-    zero30.max_i8(one30)
+    M::max(zero30, one30)
 }
 
 #[inline]
-fn compute_max0(
+#[allow(unused_parens, clippy::double_parens)] // The parentheses align the lanes.
+fn compute_max0<M: MaxOp>(
     a74: DWord,
     a30: DWord,
     g: DWord,
@@ -409,14 +1224,15 @@ fn compute_max0(
     let sum30 = (a30.saturating_add_i8(g30.saturating_add_i8(b_for30)) & a30_valid)
         | (0x80808080 & !a30_valid);
 
-    let mut max = sum74.max_i8(sum30);
-    max = max.max_i8(max >> 16);
-    max = max.max_i8(max >> 8);
+    let mut max = M::max(sum74, sum30);
+    max = M::max(max, max >> 16);
+    max = M::max(max, max >> 8);
     Llr((max.u32() & 0xFF) as i8)
 }
 
 #[inline]
-fn compute_max1(
+#[allow(unused_parens, clippy::double_parens)] // The parentheses align the lanes.
+fn compute_max1<M: MaxOp>(
     a74: DWord,
     a30: DWord,
     g: DWord,
@@ -459,9 +1275,9 @@ fn compute_max1(
     let sum30 = (a30.saturating_add_i8(g30.saturating_add_i8(b_for30)) & a30_valid)
         | (0x80808080 & (!a30_valid));
 
-    let mut max = sum74.max_i8(sum30);
-    max = max.max_i8(max >> 16);
-    max = max.max_i8(max >> 8);
+    let mut max = M::max(sum74, sum30);
+    max = M::max(max, max >> 16);
+    max = M::max(max, max >> 8);
     Llr((max.u32() & 0xFF) as i8)
 }
 
@@ -547,7 +1363,7 @@ fn scale_coeff8(values74: DWord, values30: DWord) -> DWord {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::llr_vec;
+    use crate::{encoders::rsc::RscEncoder, llr_vec};
 
     static UMTS: UmtsTrellis = UmtsTrellis;
 
@@ -588,4 +1404,318 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn sliding_window_full_block() {
+        let systematic = llr_vec![4, 4, -4, 4, 4, -4, -4, 4, -4, -4, -4];
+        let parity = llr_vec![4, -4, -4, 4, 4, -4, 4, 4, -4, -4, -4];
+        let apriori = llr_vec![0, 2, -2, 0, 0, -6, 0, 0, 0, 0, 0];
+
+        for &terminated in [true, false].iter() {
+            for &(window, training) in [(11, 0), (4, 7), (64, 0)].iter() {
+                let full = UMTS.decode(
+                    systematic.clone().into_iter(),
+                    parity.clone().into_iter(),
+                    apriori.clone().into_iter(),
+                    terminated,
+                );
+                let windowed = UmtsSlidingWindowTrellis::new(window, training).decode(
+                    systematic.clone().into_iter(),
+                    parity.clone().into_iter(),
+                    apriori.clone().into_iter(),
+                    terminated,
+                );
+                assert_eq!(full, windowed);
+            }
+        }
+    }
+
+    #[test]
+    fn sliding_window_long_block() {
+        let k = 1000;
+        let input: Vec<bool> = (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let (parity, termination) = RscEncoder::new().encode(input.iter().copied(), true);
+
+        // Weaken and flip some of the received values.
+        let llr = |(i, &x): (usize, &bool)| {
+            Llr(match (x, i % 7) {
+                (_, 3) => 0,
+                (true, 5) => -1,
+                (false, 5) => 1,
+                (true, _) => 4,
+                (false, _) => -4,
+            })
+        };
+        let systematic: Vec<Llr> = input.iter().chain(&termination).enumerate().map(llr).collect();
+        let parity: Vec<Llr> = parity.iter().enumerate().map(|(i, x)| llr((i + 2, x))).collect();
+        let apriori = vec![Llr::ZERO; k + 3];
+
+        let full = UMTS.decode(
+            systematic.iter().copied(),
+            parity.iter().copied(),
+            apriori.iter().copied(),
+            true,
+        );
+        let windowed = UmtsSlidingWindowTrellis::new(32, 32).decode(
+            systematic.iter().copied(),
+            parity.iter().copied(),
+            apriori.iter().copied(),
+            true,
+        );
+
+        assert_eq!(k + 3, windowed.len());
+        let decoded: Vec<bool> = windowed.iter().take(k).map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+        for (full, windowed) in full.iter().zip(&windowed) {
+            assert!((full.0 as i32 - windowed.0 as i32).abs() <= 4);
+        }
+    }
+
+    #[test]
+    fn radix4() {
+        let systematic =
+            llr_vec![-4, -4, -4, 4, -4, -4, 4, 4, -4, -4, -4, -4, -4, -4, 4, -4, 4, -4, 4,];
+        let parity = llr_vec![-4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, 4,];
+        let apriori = llr_vec![0, 2, -2, 0, 0, -6, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0,];
+
+        // Both an odd and an even block length.
+        for &len in [19, 18].iter() {
+            for &terminated in [true, false].iter() {
+                let radix2 = UMTS.decode(
+                    systematic.iter().copied().take(len),
+                    parity.iter().copied().take(len),
+                    apriori.iter().copied().take(len),
+                    terminated,
+                );
+                let radix4 = UmtsRadix4Trellis.decode(
+                    systematic.iter().copied().take(len),
+                    parity.iter().copied().take(len),
+                    apriori.iter().copied().take(len),
+                    terminated,
+                );
+                assert_eq!(radix2, radix4);
+            }
+        }
+    }
+
+    #[test]
+    fn radix4_saturation() {
+        for &k in [40, 41].iter() {
+            let input: Vec<bool> = (0..k).map(|i| (i * 5 + i / 4) % 7 < 3).collect();
+            let (parity, termination) = RscEncoder::new().encode(input.iter().copied(), true);
+            let bits: Vec<bool> = input.iter().chain(&termination).copied().collect();
+
+            // Large inputs saturate the path metrics, and the sum of the branch metrics of two steps
+            // would saturate if the branch metrics were not centered.
+            for &(magnitude, errors) in [(48, false), (24, true)].iter() {
+                let llr = |(i, &x): (usize, &bool)| Llr(if x ^ (errors && i % 11 == 4) { magnitude } else { -magnitude });
+                let systematic: Vec<Llr> = bits.iter().enumerate().map(llr).collect();
+                let parity: Vec<Llr> = parity.iter().enumerate().map(|(i, x)| llr((i + 5, x))).collect();
+                let apriori = vec![Llr::ZERO; bits.len()];
+
+                for &terminated in [true, false].iter() {
+                    let radix4 = UmtsRadix4Trellis.decode(
+                        systematic.iter().copied(),
+                        parity.iter().copied(),
+                        apriori.iter().copied(),
+                        terminated,
+                    );
+                    assert!(radix4.iter().zip(&bits).take(k).all(|(&llr, &bit)| llr != Llr::ZERO && llr.hard() == bit));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallel() {
+        let k = 200;
+        let input: Vec<bool> = (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let (parity, termination) = RscEncoder::new().encode(input.iter().copied(), true);
+
+        let llr = |(i, &x): (usize, &bool)| Llr(if i % 5 == 2 { 0 } else if x { 4 } else { -4 });
+        let systematic: Vec<Llr> = input.iter().chain(&termination).enumerate().map(llr).collect();
+        let parity: Vec<Llr> = parity.iter().enumerate().map(|(i, x)| llr((i + 1, x))).collect();
+        let apriori = vec![Llr::ZERO; k + 3];
+
+        let full = UMTS.decode(
+            systematic.iter().copied(),
+            parity.iter().copied(),
+            apriori.iter().copied(),
+            true,
+        );
+
+        let parallel = UmtsParallelTrellis::new(4);
+        let without_boundaries = parallel.decode(
+            systematic.iter().copied(),
+            parity.iter().copied(),
+            apriori.iter().copied(),
+            true,
+        );
+        let decoded: Vec<bool> = without_boundaries.iter().take(k).map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+
+        // The boundary metrics propagate one sub-block per decoding,
+        // so the result is the same as for the full block after decoding once per sub-block.
+        assert_ne!(full, without_boundaries);
+        let mut boundaries = BoundaryMetrics::new();
+        let mut with_boundaries = vec![];
+        for _ in 0..4 {
+            with_boundaries = parallel.decode_with_boundaries(
+                systematic.iter().copied(),
+                parity.iter().copied(),
+                apriori.iter().copied(),
+                true,
+                &mut boundaries,
+            );
+        }
+        assert_eq!(full, with_boundaries);
+
+        // The sub-blocks can be decoded in any order, e.g. concurrently.
+        let n = k + 3;
+        let mut previous = BoundaryMetrics::new();
+        for _ in 0..4 {
+            let mut next = BoundaryMetrics::new();
+            next.reset(8, parallel.sub_blocks(n));
+            let mut l_app = vec![Llr::ZERO; n];
+            for p in (0..parallel.sub_blocks(n)).rev() {
+                let range = parallel.sub_block_range(n, p);
+                let (alphas, betas) = parallel.decode_sub_block(
+                    p,
+                    &systematic,
+                    &parity,
+                    &apriori,
+                    true,
+                    &previous,
+                    &mut l_app[range],
+                );
+                next.store(p, &alphas, &betas);
+            }
+            previous = next;
+            with_boundaries = l_app;
+        }
+        assert_eq!(full, with_boundaries);
+        assert_eq!(boundaries, previous);
+    }
+
+    #[test]
+    fn log_map_max() {
+        let lhs = DWord::new_i8h([10, 10, 4, -128]);
+        let rhs = DWord::new_i8h([10, -10, 3, 127]);
+        assert_eq!([13, 10, 6, 127], LogMap::max(lhs, rhs).i8h());
+        assert_eq!([10, 10, 4, 127], MaxLog::max(lhs, rhs).i8h());
+
+        // The correction table and -inf.
+        let lhs = DWord::new_i8h([-128, -128, 0, 0]);
+        let rhs = DWord::new_i8h([-128, -127, -8, -9]);
+        assert_eq!([-128, -125, 1, 0], LogMap::max(lhs, rhs).i8h());
+    }
+
+    /// Compute `ln(1 + exp(-x))` for `x >= 0` without the standard library.
+    fn ln_1p_exp_neg(x: f64) -> f64 {
+        // exp(-x) from the Taylor series of exp(-x / 2^16) squared 16 times.
+        let y = x / 65536.0;
+        let mut exp = 1.0 - y + y * y / 2.0 - y * y * y / 6.0;
+        for _ in 0..16 {
+            exp *= exp;
+        }
+
+        // ln(1 + exp) = 2 atanh(z), where z = exp / (2 + exp) <= 1/3.
+        let z = exp / (2.0 + exp);
+        let (mut sum, mut power) = (0.0, z);
+        for i in 0..20 {
+            sum += power / (2 * i + 1) as f64;
+            power *= z * z;
+        }
+        2.0 * sum
+    }
+
+    /// The Jacobian logarithm with -inf represented as `None`.
+    fn max_star(lhs: Option<f64>, rhs: Option<f64>) -> Option<f64> {
+        match (lhs, rhs) {
+            (Some(a), Some(b)) => Some(a.max(b) + ln_1p_exp_neg((a - b).abs())),
+            (a, None) => a,
+            (None, b) => b,
+        }
+    }
+
+    /// Floating point log-MAP decoding of a terminated block, in nats.
+    fn reference_log_map(systematic: &[Llr], parity: &[Llr], apriori: &[Llr]) -> Vec<f64> {
+        let n = systematic.len();
+        let gamma = |t: usize, lane: usize| {
+            let u = if lane >= 2 { (systematic[t].0 as f64 + apriori[t].0 as f64) / 4.0 } else { 0.0 };
+            let v = if lane % 2 == 1 { parity[t].0 as f64 / 4.0 } else { 0.0 };
+            u + v
+        };
+
+        let mut alphas = vec![[None; 8]; n + 1];
+        alphas[0][0] = Some(0.0);
+        for t in 0..n {
+            for state in 0..8 {
+                for u in 0..2 {
+                    let (next, lane) = transition(state, u);
+                    let metric = alphas[t][state].map(|a| a + gamma(t, lane));
+                    alphas[t + 1][next] = max_star(alphas[t + 1][next], metric);
+                }
+            }
+        }
+
+        let mut betas = [None; 8];
+        betas[0] = Some(0.0);
+        let mut l_app = vec![0.0; n];
+        for t in (0..n).rev() {
+            let mut previous = [None; 8];
+            let mut metrics = [None; 2];
+            for state in 0..8 {
+                for (u, sum) in metrics.iter_mut().enumerate() {
+                    let (next, lane) = transition(state, u);
+                    let metric = betas[next].map(|b| b + gamma(t, lane));
+                    previous[state] = max_star(previous[state], metric);
+                    *sum = max_star(*sum, alphas[t][state].and_then(|a| metric.map(|m| a + m)));
+                }
+            }
+            l_app[t] = metrics[1].unwrap() - metrics[0].unwrap();
+            betas = previous;
+        }
+
+        l_app
+    }
+
+    #[test]
+    fn log_map_reference() {
+        let k = 40;
+        let input: Vec<bool> = (0..k).map(|i| (i * 5 + i / 4) % 7 < 3).collect();
+        let (parity, termination) = RscEncoder::new().encode(input.iter().copied(), true);
+        let bits: Vec<bool> = input.iter().chain(&termination).copied().collect();
+
+        // Noisy inputs of a few nats, with some errors.
+        let llr = |(i, &x): (usize, &bool)| {
+            let magnitude = [6, 2, 9, 4, 1, 7, 3][i % 7];
+            Llr(if x ^ (i % 9 == 4) { magnitude } else { -magnitude })
+        };
+        let systematic: Vec<Llr> = bits.iter().enumerate().map(llr).collect();
+        let parity: Vec<Llr> = parity.iter().enumerate().map(|(i, x)| llr((i + 3, x))).collect();
+        let apriori: Vec<Llr> = (0..bits.len()).map(|i| Llr([0, 2, 0, -3, 0][i % 5])).collect();
+
+        let reference = reference_log_map(&systematic, &parity, &apriori);
+        let error = |l_app: &[Llr]| {
+            l_app.iter().zip(&reference).map(|(x, y)| (x.0 as f64 / 4.0 - y).abs()).fold(0.0, f64::max)
+        };
+
+        // The log-MAP decoder is within a few quantization steps of the reference,
+        // and much closer to it than the max-log-MAP decoder.
+        let log_map = error(&UmtsLogMapTrellis.decode(
+            systematic.iter().copied(),
+            parity.iter().copied(),
+            apriori.iter().copied(),
+            true,
+        ));
+        let max_log = error(&UMTS.decode(
+            systematic.iter().copied(),
+            parity.iter().copied(),
+            apriori.iter().copied(),
+            true,
+        ));
+        assert!(log_map < 0.75);
+        assert!(4.0 * log_map < max_log);
+    }
 }
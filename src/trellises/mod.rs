@@ -1 +1,4 @@
+pub mod duobinary;
+pub mod generic;
 pub mod lte;
+pub mod packed;
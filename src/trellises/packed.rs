@@ -0,0 +1,572 @@
+//! Packed BCJR Kernels Generated from a Trellis Description
+use super::{
+    generic::{polynomial_output, shift_in, Trellis},
+    lte::branch_metrics,
+};
+use crate::{dword::DWord, simd::*, BcjrDecoder, Llr};
+use alloc::vec::Vec;
+
+/// A term of a byte routing, `(words[word] & mask)` shifted `shift` bits to the left, or to the right if negative.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Route {
+    word: usize,
+    mask: u32,
+    shift: i32,
+}
+
+/// The routing of the four lanes of a destination word from the lanes of the source words.
+/// Lanes that are moved the same distance from the same source word share a single term.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Routing {
+    routes: [Route; 4],
+    len: usize,
+}
+
+impl Routing {
+    pub(super) const EMPTY: Self = Self {
+        routes: [Route {
+            word: 0,
+            mask: 0,
+            shift: 0,
+        }; 4],
+        len: 0,
+    };
+
+    /// Create the routing where destination lane `l` is lane `sources[l].1` of the source word `sources[l].0`.
+    pub(super) const fn new(sources: [(usize, usize); 4]) -> Self {
+        let mut routing = Self::EMPTY;
+        let mut lane = 0;
+        while lane < 4 {
+            let (word, source) = sources[lane];
+            let mask = 0xFF << (8 * source);
+            let shift = 8 * (lane as i32 - source as i32);
+
+            let mut i = 0;
+            while i < routing.len
+                && !(routing.routes[i].word == word && routing.routes[i].shift == shift)
+            {
+                i += 1;
+            }
+            if i == routing.len {
+                routing.routes[i] = Route { word, mask, shift };
+                routing.len += 1;
+            } else {
+                routing.routes[i].mask |= mask;
+            }
+            lane += 1;
+        }
+        routing
+    }
+
+    /// Gather the lanes from the source `words`.
+    #[inline(always)]
+    pub(super) fn apply(&self, words: &[DWord]) -> DWord {
+        let mut result = DWord::new_u32(0);
+        for route in &self.routes[..self.len] {
+            let value = words[route.word] & route.mask;
+            result = result
+                | if route.shift >= 0 {
+                    value << route.shift as usize
+                } else {
+                    value >> (-route.shift) as usize
+                };
+        }
+        result
+    }
+}
+
+/// Max-log-MAP decoder for a rate 1/2 recursive systematic code with `4 * W` states,
+/// where the state metrics are packed four at a time into `W` words.
+/// The lane routing of the recursions is generated from the trellis tables when the decoder is created,
+/// which can be done in a `const` context such that the kernels can be specialized at compile time.
+/// State `s` is lane `s % 4` of word `s / 4`, and the metrics are normalized so that the largest is 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PackedTrellis<const W: usize> {
+    /// The routing of the alphas along the two branches into the states of each word.
+    alpha: [[Routing; 2]; W],
+    /// The routing of the branch metrics of the two branches into the states of each word.
+    alpha_gamma: [[Routing; 2]; W],
+    /// The routing of the betas along the branch with input `u` out of the states of each word.
+    beta: [[Routing; 2]; W],
+    /// The routing of the branch metrics of the branch with input `u` out of the states of each word.
+    beta_gamma: [[Routing; 2]; W],
+}
+
+/// The lane in the branch metrics for the `output` bits, see `branch_metrics()`.
+const fn gamma_lane(output: u8) -> usize {
+    2 * (output & 1) as usize + (output >> 1) as usize
+}
+
+impl<const W: usize> PackedTrellis<W> {
+    /// The state metrics where only the all-zero state is valid.
+    const ZERO_STATE: [DWord; W] = {
+        let mut states = [DWord::new_u32(0x80808080); W];
+        states[0] = DWord::new_u32(0x80808000);
+        states
+    };
+
+    /// Create the decoder from the next state and output tables, indexed by the state and the input.
+    /// Bit 0 of the output must be the systematic bit and bit 1 the parity bit.
+    pub const fn from_tables(next_state: &[[u16; 2]], output: &[[u8; 2]]) -> Self {
+        assert!(
+            W == 1 || W == 2 || W == 4,
+            "Only 4, 8 and 16 states are supported."
+        );
+        let states = 4 * W;
+        assert!(next_state.len() == states && output.len() == states);
+
+        // The previous state and input of the two branches into each state.
+        let mut incoming = [[(0usize, 0usize); 2]; 16];
+        let mut count = [0usize; 16];
+        let mut state = 0;
+        while state < states {
+            let mut u = 0;
+            while u < 2 {
+                assert!(
+                    output[state][u] >> 1 <= 1,
+                    "The code must have two outputs."
+                );
+                assert!(
+                    output[state][u] & 1 == u as u8,
+                    "The first output must be systematic."
+                );
+                let next = next_state[state][u] as usize;
+                assert!(
+                    next < states && count[next] < 2,
+                    "Each state must have two incoming branches."
+                );
+                incoming[next][count[next]] = (state, u);
+                count[next] += 1;
+                u += 1;
+            }
+            state += 1;
+        }
+
+        let mut trellis = Self {
+            alpha: [[Routing::EMPTY; 2]; W],
+            alpha_gamma: [[Routing::EMPTY; 2]; W],
+            beta: [[Routing::EMPTY; 2]; W],
+            beta_gamma: [[Routing::EMPTY; 2]; W],
+        };
+
+        let mut word = 0;
+        while word < W {
+            let mut k = 0;
+            while k < 2 {
+                let mut alpha = [(0, 0); 4];
+                let mut alpha_gamma = [(0, 0); 4];
+                let mut beta = [(0, 0); 4];
+                let mut beta_gamma = [(0, 0); 4];
+
+                let mut lane = 0;
+                while lane < 4 {
+                    let state = 4 * word + lane;
+                    let (previous, u) = incoming[state][k];
+                    alpha[lane] = (previous / 4, previous % 4);
+                    alpha_gamma[lane] = (0, gamma_lane(output[previous][u]));
+
+                    let next = next_state[state][k] as usize;
+                    beta[lane] = (next / 4, next % 4);
+                    beta_gamma[lane] = (0, gamma_lane(output[state][k]));
+                    lane += 1;
+                }
+
+                trellis.alpha[word][k] = Routing::new(alpha);
+                trellis.alpha_gamma[word][k] = Routing::new(alpha_gamma);
+                trellis.beta[word][k] = Routing::new(beta);
+                trellis.beta_gamma[word][k] = Routing::new(beta_gamma);
+                k += 1;
+            }
+            word += 1;
+        }
+
+        trellis
+    }
+
+    /// Create the decoder for the recursive systematic code with the `feedback` and `feedforward` polynomials,
+    /// in the octal notation of `Trellis`, e.g. `PackedTrellis::<2>::recursive(0o13, 0o15)` for the 3GPP code.
+    pub const fn recursive(feedback: u32, feedforward: u32) -> Self {
+        let states = 4 * W;
+        let memory = states.trailing_zeros() as usize;
+        assert!(
+            feedback >> memory == 1,
+            "The feedback must include D^0 and match the number of states."
+        );
+        assert!(feedforward >> (memory + 1) == 0);
+
+        let mut next_state = [[0u16; 2]; 16];
+        let mut output = [[0u8; 2]; 16];
+        let mut state = 0;
+        while state < states {
+            let register = state as u32;
+            let mut u = 0;
+            while u < 2 {
+                let w = polynomial_output(feedback, memory, register, u as u32);
+                let parity = polynomial_output(feedforward, memory, register, w);
+                next_state[state][u] = shift_in(memory, register, w) as u16;
+                output[state][u] = u as u8 | (parity as u8) << 1;
+                u += 1;
+            }
+            state += 1;
+        }
+
+        Self::from_tables(next_state.split_at(states).0, output.split_at(states).0)
+    }
+
+    /// Create the decoder for a `trellis` with `4 * W` states and a systematic and a parity output.
+    pub fn from_trellis(trellis: &Trellis) -> Self {
+        assert_eq!(2, trellis.outputs());
+        let next_state: Vec<[u16; 2]> = (0..trellis.states())
+            .map(|s| {
+                [
+                    trellis.next_state(s, false) as u16,
+                    trellis.next_state(s, true) as u16,
+                ]
+            })
+            .collect();
+        let output: Vec<[u8; 2]> = (0..trellis.states())
+            .map(|s| [trellis.output(s, false), trellis.output(s, true)])
+            .collect();
+        Self::from_tables(&next_state, &output)
+    }
+
+    /// Compute the alphas after a step with the branch metrics `g`, without normalization.
+    #[inline]
+    pub fn forward(&self, alphas: &[DWord; W], g: DWord) -> [DWord; W] {
+        let mut next = [DWord::new_u32(0); W];
+        for (word, next) in next.iter_mut().enumerate() {
+            let metric = |k: usize| {
+                self.alpha[word][k]
+                    .apply(alphas)
+                    .saturating_add_i8(self.alpha_gamma[word][k].apply(&[g]))
+            };
+            *next = metric(0).max_i8(metric(1));
+        }
+        next
+    }
+
+    /// Compute the betas before a step with the branch metrics `g`, without normalization.
+    #[inline]
+    pub fn backward(&self, betas: &[DWord; W], g: DWord) -> [DWord; W] {
+        Self::merge(&self.branches(betas, g))
+    }
+
+    /// Compute the llr of the input of a step from the alphas before it, the branch metrics `g`, and the betas after it.
+    #[inline]
+    pub fn llr(&self, alphas: &[DWord; W], betas: &[DWord; W], g: DWord) -> Llr {
+        Self::llr_from_branches(alphas, &self.branches(betas, g))
+    }
+
+    /// Scale the state metrics so that the largest is 0.
+    #[inline]
+    pub fn normalize(states: &mut [DWord; W]) {
+        let max = Self::max(states);
+        for state in states.iter_mut() {
+            *state = state.saturating_sub_i8(max);
+        }
+    }
+
+    /// The sum of the branch metric and the beta it leads to, for the branch with input `u` out of each state.
+    #[inline]
+    fn branches(&self, betas: &[DWord; W], g: DWord) -> [[DWord; W]; 2] {
+        let mut branches = [[DWord::new_u32(0); W]; 2];
+        for (u, branches) in branches.iter_mut().enumerate() {
+            for (word, branch) in branches.iter_mut().enumerate() {
+                *branch = self.beta[word][u]
+                    .apply(betas)
+                    .saturating_add_i8(self.beta_gamma[word][u].apply(&[g]));
+            }
+        }
+        branches
+    }
+
+    /// The betas as the largest of the two `branches` out of each state.
+    #[inline]
+    fn merge(branches: &[[DWord; W]; 2]) -> [DWord; W] {
+        let mut betas = branches[0];
+        for (beta, &one) in betas.iter_mut().zip(&branches[1]) {
+            *beta = beta.max_i8(one);
+        }
+        betas
+    }
+
+    #[inline]
+    fn llr_from_branches(alphas: &[DWord; W], branches: &[[DWord; W]; 2]) -> Llr {
+        let max = |branches: &[DWord; W]| {
+            let mut sums = *alphas;
+            for (sum, &branch) in sums.iter_mut().zip(branches) {
+                *sum = sum.saturating_add_i8(branch);
+            }
+            Llr((Self::max(&sums).u32() & 0xFF) as i8)
+        };
+        max(&branches[1]).saturating_sub(max(&branches[0]))
+    }
+
+    /// The largest of all the state metrics, in all four lanes.
+    #[inline]
+    fn max(states: &[DWord; W]) -> DWord {
+        let mut max = states[0];
+        for &state in &states[1..] {
+            max = max.max_i8(state);
+        }
+        let max = max.max_i8(max.rotate_right(16));
+        max.max_i8(max.rotate_right(8))
+    }
+}
+
+impl PackedTrellis<4> {
+    /// The 16-state constituent code of the CCSDS Turbo code with only the `1a` and `1b` parity outputs,
+    /// i.e. every third parity llr of `CcsdsRate::depuncture()`, which suffices for rates 1/2 and 1/3.
+    /// The other rates are decoded with all the parity outputs using `ccsds_trellis()`.
+    pub const CCSDS: Self = Self::recursive(0o23, 0o33);
+}
+
+impl<const W: usize> BcjrDecoder for PackedTrellis<W> {
+    fn decode<Lu: Iterator<Item = Llr>, Lv: Iterator<Item = Llr>, La: Iterator<Item = Llr>>(
+        &self,
+        systematic: Lu,
+        parity: Lv,
+        apriori: La,
+        terminated: bool,
+    ) -> Vec<Llr> {
+        let g_vector: Vec<DWord> = systematic
+            .zip(parity)
+            .zip(apriori)
+            .map(|((lu, lv), la)| branch_metrics(lu, lv, la))
+            .collect();
+        let n = g_vector.len();
+
+        // The alphas before each step, starting in the all-zero state.
+        let mut a_vector = Vec::with_capacity(n);
+        let mut alphas = Self::ZERO_STATE;
+        for &g in g_vector.iter().take(n.saturating_sub(1)) {
+            a_vector.push(alphas);
+            alphas = self.forward(&alphas, g);
+            Self::normalize(&mut alphas);
+        }
+        a_vector.push(alphas);
+
+        let mut betas = if terminated {
+            Self::ZERO_STATE
+        } else {
+            [DWord::new_u32(0); W]
+        };
+        let mut l_app = vec![Llr::ZERO; n];
+
+        for (t, (alphas, &g)) in a_vector.iter().zip(&g_vector).enumerate().rev() {
+            let branches = self.branches(&betas, g);
+            l_app[t] = Self::llr_from_branches(alphas, &branches);
+            betas = Self::merge(&branches);
+            Self::normalize(&mut betas);
+        }
+
+        l_app
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        encoders::lte::LteTurboEncoder, interleavers::lte::LteQpp, trellises::generic::ScalarBcjr,
+        TurboCodeword, TurboDecoder,
+    };
+    use streaming_iterator::StreamingIterator;
+
+    const RSC4: PackedTrellis<1> = PackedTrellis::recursive(0o7, 0o5);
+    const UMTS: PackedTrellis<2> = PackedTrellis::recursive(0o13, 0o15);
+    const RSC16: PackedTrellis<4> = PackedTrellis::recursive(0o23, 0o35);
+
+    /// The metric of each state, where state `s` is lane `s % 4` of word `s / 4`.
+    fn lanes(words: &[DWord]) -> Vec<i8> {
+        words
+            .iter()
+            .flat_map(|word| (0..4).map(move |lane| (word.u32() >> (8 * lane)) as i8))
+            .collect()
+    }
+
+    fn words<const W: usize>(lanes: &[i8]) -> [DWord; W] {
+        let mut words = [DWord::new_u32(0); W];
+        for (state, &value) in lanes.iter().enumerate() {
+            words[state / 4] = words[state / 4] | ((value as u8 as u32) << (8 * (state % 4)));
+        }
+        words
+    }
+
+    /// Compare the packed recursions with a scalar evaluation of the `trellis`.
+    fn check_recursions<const W: usize>(trellis: &Trellis, packed: &PackedTrellis<W>) {
+        let states = trellis.states();
+        let metric = |i: usize| ((i * 37 + 11) % 41) as i8 - 20;
+
+        for step in 0..8 {
+            let (lu, lv, la) = (
+                Llr(metric(step) / 2),
+                Llr(metric(step + 3) / 2),
+                Llr(metric(step + 7) / 4),
+            );
+            let gamma = |state: usize, u: bool| {
+                let bits = trellis.output(state, u);
+                let mut gamma = 0;
+                if u {
+                    gamma += la.0 + lu.0;
+                }
+                if bits & 2 != 0 {
+                    gamma += lv.0;
+                }
+                gamma
+            };
+            let g = branch_metrics(lu, lv, la);
+            let values: Vec<i8> = (0..states).map(|s| metric(s + 5 * step)).collect();
+
+            let mut alphas = vec![i8::MIN; states];
+            let mut betas = vec![i8::MIN; states];
+            let mut max = [i8::MIN; 2];
+            for state in 0..states {
+                for &u in [false, true].iter() {
+                    let next = trellis.next_state(state, u);
+                    alphas[next] = alphas[next].max(values[state] + gamma(state, u));
+                    betas[state] = betas[state].max(values[next] + gamma(state, u));
+                    max[u as usize] =
+                        max[u as usize].max(values[state] + gamma(state, u) + values[next]);
+                }
+            }
+
+            let words = words::<W>(&values);
+            assert_eq!(alphas, lanes(&packed.forward(&words, g)));
+            assert_eq!(betas, lanes(&packed.backward(&words, g)));
+            assert_eq!(
+                Llr(max[1].saturating_sub(max[0])),
+                packed.llr(&words, &words, g)
+            );
+
+            let mut normalized = words;
+            PackedTrellis::normalize(&mut normalized);
+            let largest = *values.iter().max().unwrap();
+            let expected: Vec<i8> = values.iter().map(|&x| x - largest).collect();
+            assert_eq!(expected, lanes(&normalized));
+        }
+    }
+
+    /// Compare the decoder with the scalar reference decoder for the `trellis`.
+    fn check_decode<const W: usize>(trellis: Trellis, packed: &PackedTrellis<W>) {
+        let input: Vec<bool> = (0..60).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        for &terminated in [true, false].iter() {
+            let streams = trellis.encode(&input, terminated);
+            let llrs = |bits: &[bool]| -> Vec<Llr> {
+                bits.iter()
+                    .enumerate()
+                    .map(|(i, &x)| Llr(if x ^ (i % 7 == 3) { 4 } else { -4 }))
+                    .collect()
+            };
+            let systematic = llrs(&streams[0]);
+            let parity = llrs(&streams[1]);
+            let apriori: Vec<Llr> = (0..systematic.len())
+                .map(|i| Llr((i % 5) as i8 - 2))
+                .collect();
+
+            let expected = ScalarBcjr::new(trellis.clone()).decode(
+                systematic.iter().copied(),
+                parity.iter().copied(),
+                apriori.iter().copied(),
+                terminated,
+            );
+            let actual = packed.decode(
+                systematic.iter().copied(),
+                parity.iter().copied(),
+                apriori.iter().copied(),
+                terminated,
+            );
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(
+            RSC4,
+            PackedTrellis::from_trellis(&Trellis::recursive(3, 0o7, &[0o5]))
+        );
+        assert_eq!(
+            UMTS,
+            PackedTrellis::from_trellis(&Trellis::recursive(4, 0o13, &[0o15]))
+        );
+        assert_eq!(
+            RSC16,
+            PackedTrellis::from_trellis(&Trellis::recursive(5, 0o23, &[0o35]))
+        );
+    }
+
+    #[test]
+    fn routing() {
+        // Lanes 1 and 2 move up one lane, lane 3 comes from lane 0 of the second word, and lane 0 stays.
+        let routing = Routing::new([(0, 0), (0, 0), (0, 1), (1, 0)]);
+        assert_eq!(3, routing.len);
+        assert_eq!(
+            Route {
+                word: 0,
+                mask: 0x000000FF,
+                shift: 0
+            },
+            routing.routes[0]
+        );
+        assert_eq!(
+            Route {
+                word: 0,
+                mask: 0x0000FFFF,
+                shift: 8
+            },
+            routing.routes[1]
+        );
+        assert_eq!(
+            Route {
+                word: 1,
+                mask: 0x000000FF,
+                shift: 24
+            },
+            routing.routes[2]
+        );
+
+        let words = [DWord::new_u32(0x44332211), DWord::new_u32(0x88776655)];
+        assert_eq!(0x55221111, routing.apply(&words).u32());
+    }
+
+    #[test]
+    fn recursions() {
+        check_recursions(&Trellis::recursive(3, 0o7, &[0o5]), &RSC4);
+        check_recursions(&Trellis::recursive(4, 0o13, &[0o15]), &UMTS);
+        check_recursions(&Trellis::recursive(5, 0o23, &[0o35]), &RSC16);
+    }
+
+    #[test]
+    fn decode_same_as_scalar() {
+        check_decode(Trellis::recursive(3, 0o7, &[0o5]), &RSC4);
+        check_decode(Trellis::recursive(4, 0o13, &[0o15]), &UMTS);
+        check_decode(Trellis::recursive(5, 0o23, &[0o35]), &RSC16);
+    }
+
+    #[test]
+    fn turbo_decode() {
+        let k = 40;
+        let input: Vec<bool> = (0..k).map(|i| (i * 7 + i / 3) % 5 < 2).collect();
+        let codeword = LteTurboEncoder.encode(&input, LteQpp::get(k).unwrap());
+        let llrs = |bits: &[bool]| -> Vec<Llr> {
+            bits.iter().map(|&x| Llr(if x { 4 } else { -4 })).collect()
+        };
+        let mut received = TurboCodeword {
+            systematic: llrs(&codeword.systematic),
+            first_systematic_termination: llrs(&codeword.first_systematic_termination),
+            first_parity: llrs(&codeword.first_parity),
+            second_systematic_termination: llrs(&codeword.second_systematic_termination),
+            second_parity: llrs(&codeword.second_parity),
+        };
+        for i in (0..k).step_by(3) {
+            received.systematic[i] = Llr(-received.systematic[i].0);
+        }
+
+        let mut turbo = TurboDecoder::new(UMTS);
+        let mut iterator = turbo.decode_codeword(&received, LteQpp::get(k).unwrap());
+        let l_app = iterator.nth(2).unwrap();
+        let decoded: Vec<bool> = l_app.iter().map(|x| x.hard()).collect();
+        assert_eq!(input, decoded);
+    }
+}
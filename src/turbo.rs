@@ -1,6 +1,9 @@
-use crate::{BcjrDecoder, Llr};
+use crate::{
+    dword::DWord, interleavers::Interleaver, simd::*, stopping::StopCriterion, BcjrDecoder,
+    BoundaryMetrics, Llr, TurboCodeword,
+};
 use alloc::vec::Vec;
-use core::iter::repeat;
+use core::iter::repeat_n;
 use streaming_iterator::StreamingIterator;
 
 /// A Turbo decoder.
@@ -10,9 +13,23 @@ pub struct TurboDecoder<B: BcjrDecoder> {
     /// The a-posteriori log-likelihood ratios (produced by the first decoder).
     l_app_deinterleaved: Vec<Llr>,
     la_second: Vec<Llr>,
+    /// The a-posteriori log-likelihood ratios produced by the first decoder, excluding termination.
+    l_app_first: Vec<Llr>,
+    /// The extrinsic scaling factors for each iteration.
+    extrinsic_scale: Vec<ExtrinsicScale>,
+    /// The sub-block boundary metrics kept between iterations by the first decoder.
+    first_boundaries: BoundaryMetrics,
+    /// The sub-block boundary metrics kept between iterations by the second decoder.
+    second_boundaries: BoundaryMetrics,
 }
 
-pub struct TurboDecodeIterator<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> {
+/// Scaling factor in units of 1/16 for the extrinsic information exchanged between the constituent decoders.
+/// Scaling compensates for the over-optimistic extrinsic information produced by a max-log-MAP decoder
+/// (enhanced max-log-MAP), with typical factors being 0.6875 and 0.75.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtrinsicScale(u8);
+
+pub struct TurboDecodeIterator<'a, B: BcjrDecoder, I: Interleaver> {
     /// Reference to the decoder.
     decoder: &'a mut TurboDecoder<B>,
     /// The systematic soft values.
@@ -27,6 +44,82 @@ pub struct TurboDecodeIterator<'a, B: BcjrDecoder, I: IntoIterator<Item = usize>
     second_decoder_parity: &'a [Llr],
     /// The interleaver.
     interleaver: I,
+    /// The number of completed iterations.
+    iteration: usize,
+}
+
+impl ExtrinsicScale {
+    /// No scaling.
+    pub const ONE: ExtrinsicScale = ExtrinsicScale(16);
+
+    /// Create a scaling factor of `sixteenths / 16`.
+    pub const fn from_sixteenths(sixteenths: u8) -> Self {
+        assert!(sixteenths <= 16);
+        Self(sixteenths)
+    }
+
+    /// Scale four llr's at a time.
+    /// Each llr is multiplied by the factor and rounded to the nearest integer, with ties rounded away from zero,
+    /// so that the scaling is symmetric for positive and negative llr's.
+    #[inline]
+    pub fn scale(self, values: DWord) -> DWord {
+        if self == Self::ONE {
+            return values;
+        }
+
+        // The product is accumulated from the least significant bit of the factor with halving additions,
+        // which computes `floor((x * factor + bias) / 16)` without overflowing the lanes.
+        // The bias of 8 rounds ties up for positive lanes, and the bias of 7 rounds them down for negative lanes.
+        let negative = (values >> 7) & 0x01010101;
+        let positive = negative.u32() ^ 0x01010101;
+        let mut product = DWord::new_u32(0);
+        for bit in 0..4 {
+            let term = if self.0 & (1 << bit) != 0 {
+                values
+            } else {
+                DWord::new_u32(0)
+            };
+            let bias = if bit < 3 {
+                negative
+            } else {
+                DWord::new_u32(positive)
+            };
+            product = product.saturating_add_i8(bias).half_add_i8(term);
+        }
+        product
+    }
+
+    /// Scale a single llr, with the same rounding as `scale()`.
+    #[inline]
+    fn scale_llr(self, llr: Llr) -> Llr {
+        let bias = if llr.0 < 0 { 7 } else { 8 };
+        Llr(((llr.0 as i16 * self.0 as i16 + bias) >> 4) as i8)
+    }
+
+    /// Scale a slice of llr's in place.
+    pub fn scale_llrs(self, llrs: &mut [Llr]) {
+        if self == Self::ONE {
+            return;
+        }
+
+        let mut words = llrs.chunks_exact_mut(4);
+        for word in &mut words {
+            let values = DWord::new_u32(u32::from_le_bytes([
+                word[0].0 as u8,
+                word[1].0 as u8,
+                word[2].0 as u8,
+                word[3].0 as u8,
+            ]));
+            let scaled = self.scale(values).i8le();
+            for (llr, &value) in word.iter_mut().zip(scaled.iter()) {
+                *llr = Llr(value);
+            }
+        }
+
+        for llr in words.into_remainder() {
+            *llr = self.scale_llr(*llr);
+        }
+    }
 }
 
 impl<B: BcjrDecoder> TurboDecoder<B> {
@@ -36,12 +129,24 @@ impl<B: BcjrDecoder> TurboDecoder<B> {
             bcjr,
             l_app_deinterleaved: vec![],
             la_second: vec![],
+            l_app_first: vec![],
+            extrinsic_scale: vec![],
+            first_boundaries: BoundaryMetrics::new(),
+            second_boundaries: BoundaryMetrics::new(),
         }
     }
 
+    /// Scale the extrinsic information exchanged between the constituent decoders.
+    /// The factor `per_iteration[i]` is used in iteration `i`, and the last factor is used for all following iterations.
+    pub fn with_extrinsic_scale(mut self, per_iteration: &[ExtrinsicScale]) -> Self {
+        self.extrinsic_scale = per_iteration.to_vec();
+        self
+    }
+
     /// Produce an iterator for the Turbo decoder.
     /// Each iteration in the iterator corresponds to a Turbo decoder iteration.
-    pub fn decode<'a, I: IntoIterator<Item = usize> + Clone>(
+    /// The parity parts hold `BcjrDecoder::parity_outputs()` llr's for each systematic llr, including termination.
+    pub fn decode<'a, I: Interleaver>(
         &'a mut self,
         systematic: &'a [Llr],
         first_decoder_systematic_termination: Option<&'a [Llr]>,
@@ -50,12 +155,16 @@ impl<B: BcjrDecoder> TurboDecoder<B> {
         second_decoder_parity: &'a [Llr],
         interleaver: I,
     ) -> TurboDecodeIterator<'a, B, I> {
+        assert_eq!(systematic.len(), interleaver.len());
+        let parity_outputs = self.bcjr.parity_outputs();
         assert_eq!(
-            systematic.len() + first_decoder_systematic_termination.map_or(0, |x| x.len()),
+            (systematic.len() + first_decoder_systematic_termination.map_or(0, |x| x.len()))
+                * parity_outputs,
             first_decoder_parity.len()
         );
         assert_eq!(
-            systematic.len() + second_decoder_systematic_termination.map_or(0, |x| x.len()),
+            (systematic.len() + second_decoder_systematic_termination.map_or(0, |x| x.len()))
+                * parity_outputs,
             second_decoder_parity.len()
         );
         TurboDecodeIterator {
@@ -66,22 +175,65 @@ impl<B: BcjrDecoder> TurboDecoder<B> {
             second_decoder_systematic_termination,
             second_decoder_parity,
             interleaver,
+            iteration: 0,
         }
     }
+
+    /// Produce an iterator for the Turbo decoder from a received terminated `codeword`.
+    pub fn decode_codeword<'a, I: Interleaver>(
+        &'a mut self,
+        codeword: &'a TurboCodeword<Llr>,
+        interleaver: I,
+    ) -> TurboDecodeIterator<'a, B, I> {
+        self.decode(
+            &codeword.systematic,
+            Some(&codeword.first_systematic_termination),
+            &codeword.first_parity,
+            Some(&codeword.second_systematic_termination),
+            &codeword.second_parity,
+            interleaver,
+        )
+    }
+}
+
+impl<'a, B: BcjrDecoder, I: Interleaver> TurboDecodeIterator<'a, B, I> {
+    /// Run Turbo decoder iterations until the `criterion` is satisfied, or `max_iterations` have been run.
+    /// Returns the number of iterations that were run.
+    /// The a-posteriori llr's from the last iteration are available from `get()`.
+    pub fn decode_until<S: StopCriterion>(
+        &mut self,
+        max_iterations: usize,
+        criterion: &mut S,
+    ) -> usize {
+        criterion.reset();
+
+        for iteration in 1..=max_iterations {
+            self.advance();
+            if criterion.should_stop(&self.decoder.l_app_first, &self.decoder.l_app_deinterleaved) {
+                return iteration;
+            }
+        }
+
+        max_iterations
+    }
+
+    /// Get the a-posteriori llr's produced by the first decoder in the last iteration, excluding termination.
+    pub fn first_decoder_l_app(&self) -> &[Llr] {
+        &self.decoder.l_app_first
+    }
 }
 
-impl<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> Drop
-    for TurboDecodeIterator<'a, B, I>
-{
+impl<'a, B: BcjrDecoder, I: Interleaver> Drop for TurboDecodeIterator<'a, B, I> {
     fn drop(&mut self) {
         self.decoder.l_app_deinterleaved.clear();
         self.decoder.la_second.clear();
+        self.decoder.l_app_first.clear();
+        self.decoder.first_boundaries.clear();
+        self.decoder.second_boundaries.clear();
     }
 }
 
-impl<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> StreamingIterator
-    for TurboDecodeIterator<'a, B, I>
-{
+impl<'a, B: BcjrDecoder, I: Interleaver> StreamingIterator for TurboDecodeIterator<'a, B, I> {
     type Item = [Llr];
 
     fn advance(&mut self) {
@@ -92,6 +244,14 @@ impl<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> StreamingIterato
             .second_decoder_systematic_termination
             .map_or(0, |x| x.len());
 
+        let extrinsic_scale = self
+            .decoder
+            .extrinsic_scale
+            .get(self.iteration)
+            .or_else(|| self.decoder.extrinsic_scale.last())
+            .copied()
+            .unwrap_or(ExtrinsicScale::ONE);
+
         // Prepare the input symbols for the first decoder. It consists of:
         // * The systematic llr's.
         // * The parity llr's.
@@ -111,15 +271,29 @@ impl<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> StreamingIterato
             // Compute the extrinsic information from the a-posteriori LLR (Lapp) from second decoder,
             // to be used now as the a-priori LLR for the first decoder.
             // This is eqn. 28. in the turbo.pdf reference.
-            let mut la_first = vec![Llr::ZERO; self.systematic.len() + first_term_len];
-            for (index, int_index) in self.interleaver.clone().into_iter().enumerate() {
-                let l_app = l_app_deinterleaved[int_index];
-                let l_a = la_second[index];
-                let l_u = self.systematic[int_index];
-                let l_e = l_app.saturating_sub(l_a).saturating_sub(l_u);
-
-                la_first[int_index] = l_e;
-            }
+            // The a-priori llr of the second decoder is gathered using the de-interleaved index,
+            // or scattered using the interleaved index if the interleaver has no fast inverse.
+            let mut la_first: Vec<Llr> = if self.interleaver.has_fast_inverse() {
+                self.interleaver
+                    .iter_inv()
+                    .zip(l_app_deinterleaved.iter().zip(self.systematic))
+                    .map(|(index, (&l_app, &l_u))| {
+                        let l_a = la_second[index];
+                        l_app.saturating_sub(l_a).saturating_sub(l_u)
+                    })
+                    .collect()
+            } else {
+                let mut la_first = vec![Llr::ZERO; self.systematic.len()];
+                for (index, int_index) in self.interleaver.iter().enumerate() {
+                    let l_app = l_app_deinterleaved[int_index];
+                    let l_a = la_second[index];
+                    let l_u = self.systematic[int_index];
+                    la_first[int_index] = l_app.saturating_sub(l_a).saturating_sub(l_u);
+                }
+                la_first
+            };
+
+            extrinsic_scale.scale_llrs(&mut la_first);
 
             // The extrinsic information is not valid for the termination.
             for _ in 0..first_term_len {
@@ -136,7 +310,7 @@ impl<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> StreamingIterato
             .flatten();
 
         // Run the BCJR algorithm and compute the a-posteriori llr's Lapp for the first decoder.
-        let l_app_first = self.decoder.bcjr.decode(
+        let l_app_first = self.decoder.bcjr.decode_with_boundaries(
             self.systematic
                 .iter()
                 .chain(systematic_termination)
@@ -144,15 +318,15 @@ impl<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> StreamingIterato
             self.first_decoder_parity.iter().copied(),
             la_first.iter().copied(),
             self.first_decoder_systematic_termination.is_some(),
+            &mut self.decoder.first_boundaries,
         );
 
         // Compute the extrinsic information from the a-posteriori LLR (Lapp) from the first decoder,
         // to be used as the a priori LLR for the second decoder.
         // This is eqn. 28 in the turbo.pdf reference.
-        let la_second: Vec<Llr> = self
+        let mut la_second: Vec<Llr> = self
             .interleaver
-            .clone()
-            .into_iter()
+            .iter()
             .map(|int_index| {
                 let l_app = l_app_first[int_index];
                 let l_a = la_first[int_index];
@@ -162,8 +336,9 @@ impl<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> StreamingIterato
                 l_app.saturating_sub(l_a).saturating_sub(l_u)
             })
             // The extrinsic information is not valid for the termination.
-            .chain(repeat(Llr::ZERO).take(second_term_len))
+            .chain(repeat_n(Llr::ZERO, second_term_len))
             .collect();
+        extrinsic_scale.scale_llrs(&mut la_second);
 
         // Compute Lapp.
         let systematic_termination = self
@@ -173,25 +348,39 @@ impl<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> StreamingIterato
             .flatten();
 
         // Compute the a-posteriori llr's Lapp for the second decoder.
-        let l_app_second = self.decoder.bcjr.decode(
+        let systematic = self.systematic;
+        let l_app_second = self.decoder.bcjr.decode_with_boundaries(
             self.interleaver
-                .clone()
-                .into_iter()
-                .map(|int_index| self.systematic[int_index])
+                .iter()
+                .map(|int_index| systematic[int_index])
                 .chain(systematic_termination.copied()),
             self.second_decoder_parity.iter().copied(),
             la_second.iter().copied(),
             self.second_decoder_systematic_termination.is_some(),
+            &mut self.decoder.second_boundaries,
         );
 
         // De-interleave Lapp for decision making.
-        let mut l_app = vec![Llr::ZERO; self.systematic.len()];
-        for (index, int_index) in self.interleaver.clone().into_iter().enumerate() {
-            l_app[int_index] = l_app_second[index];
-        }
+        let l_app: Vec<Llr> = if self.interleaver.has_fast_inverse() {
+            self.interleaver
+                .iter_inv()
+                .map(|index| l_app_second[index])
+                .collect()
+        } else {
+            let mut l_app = vec![Llr::ZERO; self.systematic.len()];
+            for (index, int_index) in self.interleaver.iter().enumerate() {
+                l_app[int_index] = l_app_second[index];
+            }
+            l_app
+        };
+
+        let mut l_app_first = l_app_first;
+        l_app_first.truncate(self.systematic.len());
 
         self.decoder.la_second = la_second;
         self.decoder.l_app_deinterleaved = l_app;
+        self.decoder.l_app_first = l_app_first;
+        self.iteration += 1;
     }
 
     fn get(&self) -> Option<&Self::Item> {
@@ -201,7 +390,11 @@ impl<'a, B: BcjrDecoder, I: IntoIterator<Item = usize> + Clone> StreamingIterato
 
 #[cfg(any(test, target_tests))]
 pub mod tests {
-    use crate::{interleavers::qpp::Qpp, llr_vec, trellises::lte::UmtsTrellis};
+    use crate::{
+        interleavers::qpp::Qpp,
+        llr_vec,
+        trellises::lte::{UmtsParallelTrellis, UmtsTrellis},
+    };
 
     use super::*;
 
@@ -253,5 +446,176 @@ pub mod tests {
 
         assert!(turbo.l_app_deinterleaved.is_empty());
         assert!(turbo.la_second.is_empty());
+        assert!(turbo.l_app_first.is_empty());
+        assert!(turbo.first_boundaries.is_empty());
+        assert!(turbo.second_boundaries.is_empty());
+    }
+
+    #[test]
+    fn extrinsic_scale() {
+        let values = DWord::new_i8h([100, -100, 7, -1]);
+
+        assert_eq!([100, -100, 7, -1], ExtrinsicScale::ONE.scale(values).i8h());
+        assert_eq!(
+            [75, -75, 5, -1],
+            ExtrinsicScale::from_sixteenths(12).scale(values).i8h()
+        );
+        assert_eq!(
+            [69, -69, 5, -1],
+            ExtrinsicScale::from_sixteenths(11).scale(values).i8h()
+        );
+        assert_eq!(
+            [0, 0, 0, 0],
+            ExtrinsicScale::from_sixteenths(0).scale(values).i8h()
+        );
+
+        assert_eq!(
+            [0, 0, 2, -2],
+            ExtrinsicScale::from_sixteenths(4)
+                .scale(DWord::new_i8h([1, -1, 6, -6]))
+                .i8h()
+        );
+
+        let mut llrs = llr_vec![100, -100, 7, -1, 127, -127, -128];
+        ExtrinsicScale::from_sixteenths(12).scale_llrs(&mut llrs);
+        assert_eq!(llr_vec![75, -75, 5, -1, 95, -95, -96], llrs);
+    }
+
+    #[test]
+    fn extrinsic_scale_all_values() {
+        for sixteenths in 0..=16 {
+            let scale = ExtrinsicScale::from_sixteenths(sixteenths);
+            let expected: Vec<Llr> = (i8::MIN..=i8::MAX)
+                .map(|x| {
+                    let magnitude = ((x as i16).abs() * sixteenths as i16 + 8) >> 4;
+                    Llr(((x as i16).signum() * magnitude) as i8)
+                })
+                .collect();
+
+            let mut llrs: Vec<Llr> = (i8::MIN..=i8::MAX).map(Llr).collect();
+            scale.scale_llrs(&mut llrs[..255]);
+            assert_eq!(expected[..255], llrs[..255]);
+
+            let scaled = scale.scale(DWord::new_i8h([-128, -1, 0, 127])).i8h();
+            assert_eq!(
+                [
+                    expected[0].0,
+                    expected[127].0,
+                    expected[128].0,
+                    expected[255].0
+                ],
+                scaled
+            );
+        }
+    }
+
+    #[test]
+    fn decode_excel_example_scaled() {
+        let systematic = llr_vec![-4, -4, -4, 4, -4, -4, 4, 4, -4, -4, -4, -4, -4, -4, 4, -4,];
+        let first_decoder_systematic_termination = llr_vec![4, -4, 4,];
+        let first_decoder_parity =
+            llr_vec![-4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, 4,];
+        let second_decoder_systematic_termination = llr_vec![-4, -4, -4,];
+        let second_decoder_parity =
+            llr_vec![-4, -4, -4, 4, 4, 4, -4, 4, 4, -4, -4, 4, -4, 4, -4, 4, -4, -4, -4,];
+
+        let decode = |scale: &[ExtrinsicScale]| {
+            let mut turbo = TurboDecoder::new(UmtsTrellis).with_extrinsic_scale(scale);
+            let mut iterator = turbo.decode(
+                &systematic,
+                Some(&first_decoder_systematic_termination),
+                &first_decoder_parity,
+                Some(&second_decoder_systematic_termination),
+                &second_decoder_parity,
+                Qpp::new(16, 1, 4),
+            );
+            iterator.nth(1).unwrap().to_vec()
+        };
+
+        let unscaled = decode(&[]);
+        assert_eq!(unscaled, decode(&[ExtrinsicScale::ONE]));
+
+        let scaled = decode(&[
+            ExtrinsicScale::from_sixteenths(11),
+            ExtrinsicScale::from_sixteenths(12),
+        ]);
+        assert_ne!(unscaled, scaled);
+        assert!(scaled
+            .iter()
+            .zip(&unscaled)
+            .all(|(a, b)| a.hard() == b.hard()));
+    }
+
+    fn excel_example_iterations<I: Interleaver>(interleaver: I, count: usize) -> Vec<Vec<Llr>> {
+        let systematic = llr_vec![-4, -4, -4, 4, -4, -4, 4, 4, -4, -4, -4, -4, -4, -4, 4, -4,];
+        let first_decoder_systematic_termination = llr_vec![4, -4, 4,];
+        let first_decoder_parity =
+            llr_vec![-4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, 4,];
+        let second_decoder_systematic_termination = llr_vec![-4, -4, -4,];
+        let second_decoder_parity =
+            llr_vec![-4, -4, -4, 4, 4, 4, -4, 4, 4, -4, -4, 4, -4, 4, -4, 4, -4, -4, -4,];
+
+        let mut turbo = TurboDecoder::new(UmtsTrellis);
+        let mut iterator = turbo.decode(
+            &systematic,
+            Some(&first_decoder_systematic_termination),
+            &first_decoder_parity,
+            Some(&second_decoder_systematic_termination),
+            &second_decoder_parity,
+            interleaver,
+        );
+
+        let mut iterations = vec![];
+        while let Some(l_app) = iterator.next() {
+            iterations.push(l_app.to_vec());
+            if iterations.len() == count {
+                break;
+            }
+        }
+        iterations
+    }
+
+    #[test]
+    fn decode_excel_example_scatter() {
+        // De-interleaving scatters if the interleaver has no fast inverse, with the same result as gathering.
+        assert!(!Qpp::new(16, 1, 4).has_fast_inverse());
+        assert_eq!(
+            excel_example_iterations(Qpp::new(16, 1, 4).with_inverse(), 3),
+            excel_example_iterations(Qpp::new(16, 1, 4), 3)
+        );
+    }
+
+    #[test]
+    fn decode_excel_example_parallel() {
+        let systematic = llr_vec![-4, -4, -4, 4, -4, -4, 4, 4, -4, -4, -4, -4, -4, -4, 4, -4,];
+        let first_decoder_systematic_termination = llr_vec![4, -4, 4,];
+        let first_decoder_parity =
+            llr_vec![-4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, -4, -4, -4, 4, 4, 4, 4,];
+        let second_decoder_systematic_termination = llr_vec![-4, -4, -4,];
+        let second_decoder_parity =
+            llr_vec![-4, -4, -4, 4, 4, 4, -4, 4, 4, -4, -4, 4, -4, 4, -4, 4, -4, -4, -4,];
+
+        let mut turbo = TurboDecoder::new(UmtsParallelTrellis::new(2));
+        let mut iterator = turbo.decode(
+            &systematic,
+            Some(&first_decoder_systematic_termination),
+            &first_decoder_parity,
+            Some(&second_decoder_systematic_termination),
+            &second_decoder_parity,
+            Qpp::new(16, 1, 4),
+        );
+
+        let l_app = iterator.nth(2).unwrap();
+        assert!(l_app
+            .iter()
+            .zip(&systematic)
+            .all(|(a, b)| a.hard() == b.hard()));
+        assert!(!iterator.decoder.first_boundaries.is_empty());
+        assert!(!iterator.decoder.second_boundaries.is_empty());
+
+        drop(iterator);
+
+        assert!(turbo.first_boundaries.is_empty());
+        assert!(turbo.second_boundaries.is_empty());
     }
 }